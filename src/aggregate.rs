@@ -0,0 +1,331 @@
+use glob::Pattern;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::config::GroupItem;
+use crate::graph::{Edge, EdgeKind, Graph, Node, NodeType, SCHEMA_VERSION};
+use crate::metrics::attach_metrics;
+
+/// Describes how internal modules are collapsed into group nodes.
+///
+/// Explicit groups are checked first, in order. Modules that don't match any
+/// group fall back to their directory, truncated to `depth` components. When
+/// neither applies, the module is kept as its own node.
+pub struct Grouping {
+    depth: Option<usize>,
+    /// Group names with their compiled patterns
+    groups: Vec<(String, Pattern)>,
+}
+
+impl Grouping {
+    pub fn new(depth: Option<usize>, groups: &[GroupItem]) -> Result<Self, Box<dyn Error>> {
+        let groups = groups
+            .iter()
+            .map(|group| match Pattern::new(&group.pattern) {
+                Ok(pattern) => Ok((group.name.clone(), pattern)),
+                Err(error) => Err(format!(
+                    "Invalid pattern {:?} for group {:?}: {}",
+                    group.pattern, group.name, error
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Grouping { depth, groups })
+    }
+
+    fn group_key(&self, path_relative: &str) -> Option<String> {
+        for (name, pattern) in self.groups.iter() {
+            if pattern.matches(path_relative) {
+                return Some(name.clone());
+            }
+        }
+
+        self.depth
+            .map(|depth| get_directory_prefix(path_relative, depth))
+    }
+}
+
+pub fn aggregate_graph(graph: &Graph, grouping: &Grouping) -> Graph {
+    let mut nodes: Vec<Node> = vec![];
+    let mut group_ids_by_key: HashMap<String, String> = HashMap::new();
+    let mut new_ids_by_old_id: HashMap<&str, String> = HashMap::new();
+
+    for node in graph.nodes.iter() {
+        let group_key = if node.node_type.is_internal() {
+            grouping.group_key(&node.path_relative)
+        } else {
+            None
+        };
+
+        let new_id = match group_key {
+            Some(group_key) => group_ids_by_key
+                .entry(group_key.clone())
+                .or_insert_with(|| {
+                    let group_node = create_group_node(graph, node, &group_key);
+                    let id = group_node.id.clone();
+                    nodes.push(group_node);
+                    id
+                })
+                .clone(),
+            None => {
+                let mut kept_node = node.clone();
+                kept_node.metrics = None;
+                nodes.push(kept_node);
+                node.id.clone()
+            }
+        };
+        new_ids_by_old_id.insert(&node.id, new_id);
+    }
+
+    // Edges of different kinds, like a dependency and a dev-dependency, stay apart
+    let mut weights: HashMap<(String, String, Option<EdgeKind>), usize> = HashMap::new();
+    let mut edge_order: Vec<(String, String, Option<EdgeKind>)> = vec![];
+    for edge in graph.edges.iter() {
+        let source_id = &new_ids_by_old_id[edge.source_id.as_str()];
+        let target_id = &new_ids_by_old_id[edge.target_id.as_str()];
+        if source_id == target_id {
+            continue;
+        }
+
+        let key = (source_id.clone(), target_id.clone(), edge.kind);
+        let weight = weights.entry(key.clone()).or_insert(0);
+        if *weight == 0 {
+            edge_order.push(key);
+        }
+        *weight += edge.weight.unwrap_or(1);
+    }
+
+    let edges: Vec<Edge> = edge_order
+        .into_iter()
+        .map(|key| {
+            let weight = weights[&key];
+            let (source_id, target_id, kind) = key;
+            Edge {
+                id: Uuid::new_v4().to_string(),
+                source_id,
+                target_id,
+                weight: Some(weight),
+                kind,
+            }
+        })
+        .collect();
+
+    let mut aggregated = Graph {
+        schema_version: SCHEMA_VERSION,
+        title: graph.title.clone(),
+        language: graph.language.clone(),
+        nodes,
+        edges,
+    };
+    attach_metrics(&mut aggregated);
+
+    aggregated
+}
+
+fn create_group_node(graph: &Graph, member: &Node, group_key: &str) -> Node {
+    // Recover the project root from the first member, so groups point at real directories
+    let root = member
+        .path_absolute
+        .strip_suffix(&member.path_relative)
+        .unwrap_or("");
+    let is_directory = graph
+        .nodes
+        .iter()
+        .any(|node| node.path_relative.starts_with(&format!("{}/", group_key)));
+
+    let (path_relative, path_absolute) = if is_directory {
+        let path_absolute = Path::new(root).join(group_key);
+        (
+            group_key.to_string(),
+            path_absolute.to_str().unwrap().to_string(),
+        )
+    } else {
+        ("".to_string(), "".to_string())
+    };
+
+    Node {
+        id: Uuid::new_v4().to_string(),
+        node_type: NodeType::Group,
+        label: group_key.to_string(),
+        path_absolute,
+        path_relative,
+        metrics: None,
+        size: None,
+    }
+}
+
+/// Returns the directory containing a module, limited to `depth` components.
+///
+/// Modules in the project root are grouped under ".".
+pub fn get_directory_prefix(path_relative: &str, depth: usize) -> String {
+    let parent = Path::new(path_relative).parent();
+    let components: Vec<&str> = parent
+        .iter()
+        .flat_map(|parent| parent.iter())
+        .map(|component| component.to_str().unwrap())
+        .take(depth)
+        .collect();
+
+    if components.is_empty() {
+        ".".to_string()
+    } else {
+        components.join("/")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_node(id: &str, node_type: NodeType, path_relative: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            node_type,
+            label: id.to_string(),
+            path_absolute: format!("/path/to/repo/{}", path_relative),
+            path_relative: path_relative.to_string(),
+            metrics: None,
+            size: None,
+        }
+    }
+
+    fn test_edge(source_id: &str, target_id: &str) -> Edge {
+        Edge {
+            id: format!("{}->{}", source_id, target_id),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            weight: None,
+            kind: None,
+        }
+    }
+
+    fn test_graph() -> Graph {
+        Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: vec![
+                test_node("a", NodeType::Internal, "src/features/auth/login.ts"),
+                test_node("b", NodeType::Internal, "src/features/auth/logout.ts"),
+                test_node("c", NodeType::Internal, "src/common/api.ts"),
+                test_node("d", NodeType::Internal, "index.ts"),
+                test_node("react", NodeType::External, ""),
+            ],
+            edges: vec![
+                test_edge("a", "b"),
+                test_edge("a", "c"),
+                test_edge("b", "c"),
+                test_edge("d", "a"),
+                test_edge("a", "react"),
+                test_edge("b", "react"),
+            ],
+        }
+    }
+
+    fn find_node<'a>(graph: &'a Graph, label: &str) -> &'a Node {
+        graph
+            .nodes
+            .iter()
+            .find(|node| node.label == label)
+            .unwrap_or_else(|| panic!("no node labeled {}", label))
+    }
+
+    fn edge_weight(graph: &Graph, source_label: &str, target_label: &str) -> Option<usize> {
+        let source = find_node(graph, source_label);
+        let target = find_node(graph, target_label);
+        graph
+            .edges
+            .iter()
+            .find(|edge| edge.source_id == source.id && edge.target_id == target.id)
+            .and_then(|edge| edge.weight)
+    }
+
+    #[test]
+    fn test_get_directory_prefix() {
+        assert_eq!(
+            get_directory_prefix("src/features/auth/login.ts", 2),
+            "src/features"
+        );
+        assert_eq!(get_directory_prefix("src/index.ts", 2), "src");
+        assert_eq!(get_directory_prefix("index.ts", 2), ".");
+    }
+
+    #[test]
+    fn test_aggregate_graph_by_depth() {
+        let grouping = Grouping::new(Some(2), &[]).unwrap();
+        let aggregated = aggregate_graph(&test_graph(), &grouping);
+
+        assert_eq!(aggregated.nodes.len(), 4);
+        assert_eq!(
+            find_node(&aggregated, "src/features").path_relative,
+            "src/features"
+        );
+        assert_eq!(
+            edge_weight(&aggregated, "src/features", "src/common"),
+            Some(2)
+        );
+        assert_eq!(edge_weight(&aggregated, "src/features", "react"), Some(2));
+        assert_eq!(edge_weight(&aggregated, ".", "src/features"), Some(1));
+        assert_eq!(aggregated.edges.len(), 3);
+    }
+
+    #[test]
+    fn test_aggregate_graph_by_explicit_groups() {
+        let groups = vec![GroupItem {
+            name: "auth".to_string(),
+            pattern: "src/features/auth/**".to_string(),
+        }];
+        let grouping = Grouping::new(None, &groups).unwrap();
+        let aggregated = aggregate_graph(&test_graph(), &grouping);
+
+        assert_eq!(aggregated.nodes.len(), 4);
+        assert_eq!(find_node(&aggregated, "auth").path_relative, "");
+        assert_eq!(edge_weight(&aggregated, "auth", "c"), Some(2));
+        assert_eq!(edge_weight(&aggregated, "d", "auth"), Some(1));
+    }
+
+    #[test]
+    fn test_grouping_with_invalid_pattern() {
+        let groups = vec![GroupItem {
+            name: "auth".to_string(),
+            pattern: "src/[auth".to_string(),
+        }];
+
+        let error = Grouping::new(None, &groups).err().unwrap();
+        assert!(error
+            .to_string()
+            .starts_with("Invalid pattern \"src/[auth\" for group \"auth\""));
+    }
+
+    #[test]
+    fn test_aggregate_graph_keeps_edge_kinds() {
+        let mut graph = test_graph();
+        let edge = |source_id: &str, kind: EdgeKind| Edge {
+            kind: Some(kind),
+            ..test_edge(source_id, "react")
+        };
+        graph.edges = vec![
+            edge("a", EdgeKind::Normal),
+            edge("b", EdgeKind::Normal),
+            edge("c", EdgeKind::Dev),
+        ];
+        let grouping = Grouping::new(Some(1), &[]).unwrap();
+
+        let aggregated = aggregate_graph(&graph, &grouping);
+
+        let kinds: Vec<(Option<EdgeKind>, Option<usize>)> = aggregated
+            .edges
+            .iter()
+            .map(|edge| (edge.kind, edge.weight))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (Some(EdgeKind::Normal), Some(2)),
+                (Some(EdgeKind::Dev), Some(1))
+            ]
+        );
+    }
+}
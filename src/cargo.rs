@@ -0,0 +1,500 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::graph::{Edge, EdgeKind, Graph, Node, NodeType, SCHEMA_VERSION};
+use crate::metrics::attach_metrics;
+use crate::progress::progress;
+use crate::source::normalize_path;
+
+/// Dependency tables in Cargo.toml, and the kind of edge each one makes.
+const DEPENDENCY_TABLES: [(&str, EdgeKind); 3] = [
+    ("dependencies", EdgeKind::Normal),
+    ("dev-dependencies", EdgeKind::Dev),
+    ("build-dependencies", EdgeKind::Build),
+];
+
+/// A package in the workspace, or a path dependency of one.
+struct Package {
+    name: String,
+    /// Absolute directory of the package
+    directory: PathBuf,
+    manifest: toml::Table,
+}
+
+/// A dependency as written in a manifest, after following `workspace = true`.
+struct Dependency {
+    name: String,
+    kind: EdgeKind,
+    /// Absolute directory of a path dependency
+    directory: Option<PathBuf>,
+}
+
+/// Generates a graph with one node per crate, from the Cargo workspace or
+/// package at `config.path`.
+///
+/// Only the manifests are read, so this works without running cargo or
+/// fetching dependencies. Workspace members and path dependencies are
+/// internal nodes, and everything else is external, as are path
+/// dependencies whose manifest is missing.
+pub fn generate_crate_graph(config: &Config) -> Result<Graph, Box<dyn Error>> {
+    if !config.language.eq_ignore_ascii_case("rust") {
+        return Err(format!(
+            "Crate graphs need `language: rust` in the config, not {:?}",
+            config.language
+        )
+        .into());
+    }
+
+    // Path dependencies may point above the root, so `..` has to be resolved
+    // against where it really is
+    let root_directory = fs::canonicalize(&config.path)
+        .map_err(|error| format!("Couldn't read {}: {}", config.path, error))?;
+    let root_directory = root_directory.as_path();
+    let root_manifest = read_manifest(root_directory)?;
+
+    progress!("Reading workspace members...");
+    let mut packages = vec![];
+    if root_manifest.contains_key("package") {
+        packages.push(read_package(root_directory)?);
+    }
+    for member_directory in workspace_members(root_directory, &root_manifest)? {
+        if member_directory.as_os_str().is_empty() && root_manifest.contains_key("package") {
+            continue;
+        }
+        packages.push(read_package(&root_directory.join(member_directory))?);
+    }
+    progress!("Found {} crates.", packages.len());
+
+    let workspace_dependencies = root_manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("dependencies"))
+        .and_then(|dependencies| dependencies.as_table())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut nodes: Vec<Node> = vec![];
+    let mut node_ids_by_directory: HashMap<PathBuf, String> = HashMap::new();
+    let mut node_ids_by_name: HashMap<String, String> = HashMap::new();
+    let mut edges: Vec<Edge> = vec![];
+    // A crate listed in several tables, like two `target.*` ones, makes one edge
+    let mut seen_edges: HashSet<(String, String, EdgeKind)> = HashSet::new();
+
+    for package in packages.iter() {
+        let node = package_node(root_directory, package);
+        node_ids_by_directory.insert(package.directory.clone(), node.id.clone());
+        node_ids_by_name.insert(package.name.clone(), node.id.clone());
+        nodes.push(node);
+    }
+
+    // Path dependencies outside the workspace are added to the end as
+    // they're found, so their own dependencies are read too
+    let mut index = 0;
+    while index < packages.len() {
+        let source_id = node_ids_by_directory[&packages[index].directory].clone();
+        let dependencies =
+            package_dependencies(&packages[index], &workspace_dependencies, root_directory);
+
+        for dependency in dependencies {
+            let directory = dependency
+                .directory
+                .as_ref()
+                .filter(|directory| directory.join("Cargo.toml").exists());
+            let target_id = match directory {
+                Some(directory) => match node_ids_by_directory.get(directory) {
+                    Some(node_id) => node_id.clone(),
+                    None => {
+                        let package = read_package(directory)?;
+                        let node = package_node(root_directory, &package);
+                        let node_id = node.id.clone();
+                        node_ids_by_directory.insert(directory.clone(), node_id.clone());
+                        node_ids_by_name.insert(package.name.clone(), node_id.clone());
+                        nodes.push(node);
+                        packages.push(package);
+                        node_id
+                    }
+                },
+                None => match node_ids_by_name.get(&dependency.name) {
+                    Some(node_id) => node_id.clone(),
+                    None => {
+                        let node = Node {
+                            id: Uuid::new_v4().to_string(),
+                            node_type: NodeType::External,
+                            label: dependency.name.clone(),
+                            path_absolute: "".to_string(),
+                            path_relative: "".to_string(),
+                            metrics: None,
+                            size: None,
+                        };
+                        let node_id = node.id.clone();
+                        node_ids_by_name.insert(dependency.name.clone(), node_id.clone());
+                        nodes.push(node);
+                        node_id
+                    }
+                },
+            };
+
+            if !seen_edges.insert((source_id.clone(), target_id.clone(), dependency.kind)) {
+                continue;
+            }
+            edges.push(Edge {
+                id: Uuid::new_v4().to_string(),
+                source_id: source_id.clone(),
+                target_id,
+                weight: None,
+                kind: Some(dependency.kind),
+            });
+        }
+        index += 1;
+    }
+    progress!("Found {} dependencies between crates.", edges.len());
+
+    let mut graph = Graph {
+        schema_version: SCHEMA_VERSION,
+        title: config.title.clone(),
+        language: Some("rust".to_string()),
+        nodes,
+        edges,
+    };
+    attach_metrics(&mut graph);
+
+    Ok(graph)
+}
+
+fn read_manifest(directory: &Path) -> Result<toml::Table, Box<dyn Error>> {
+    let manifest_path = directory.join("Cargo.toml");
+    let contents = fs::read_to_string(&manifest_path)
+        .map_err(|error| format!("Couldn't read {}: {}", manifest_path.display(), error))?;
+
+    Ok(contents.parse()?)
+}
+
+fn read_package(directory: &Path) -> Result<Package, Box<dyn Error>> {
+    let manifest = read_manifest(directory)?;
+    let name = manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str())
+        .ok_or_else(|| {
+            format!(
+                "No package name in {}",
+                directory.join("Cargo.toml").display()
+            )
+        })?
+        .to_string();
+
+    Ok(Package {
+        name,
+        directory: directory.to_path_buf(),
+        manifest,
+    })
+}
+
+fn package_node(root_directory: &Path, package: &Package) -> Node {
+    let path_relative = relative_path(&package.directory, root_directory);
+    let path_relative = match path_relative.to_str().unwrap() {
+        "" => ".".to_string(),
+        directory => directory.to_string(),
+    };
+
+    Node {
+        id: Uuid::new_v4().to_string(),
+        node_type: NodeType::Internal,
+        label: package.name.clone(),
+        path_absolute: package.directory.to_str().unwrap().to_string(),
+        path_relative,
+        metrics: None,
+        size: None,
+    }
+}
+
+/// The path from `base` to `path`, going up with `..` where `path` isn't inside `base`.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let shared = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    base.components()
+        .skip(shared)
+        .map(|_| Component::ParentDir)
+        .chain(path.components().skip(shared))
+        .collect()
+}
+
+/// Lists the directories of the workspace members, relative to the root,
+/// expanding globs in `members` and leaving out `exclude`.
+fn workspace_members(
+    root_directory: &Path,
+    root_manifest: &toml::Table,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let Some(workspace) = root_manifest.get("workspace") else {
+        return Ok(vec![]);
+    };
+    let strings = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str())
+            .map(|value| value.to_string())
+            .collect()
+    };
+    let excluded: Vec<PathBuf> = strings("exclude")
+        .iter()
+        .map(|path| normalize_path(Path::new(path)))
+        .collect();
+
+    let mut members = vec![];
+    for member in strings("members") {
+        let pattern = root_directory.join(&member);
+        for path in glob::glob(pattern.to_str().unwrap())?.filter_map(Result::ok) {
+            if !path.join("Cargo.toml").exists() {
+                continue;
+            }
+            let directory = normalize_path(path.strip_prefix(root_directory)?);
+            if !excluded.contains(&directory) && !members.contains(&directory) {
+                members.push(directory);
+            }
+        }
+    }
+    members.sort();
+
+    Ok(members)
+}
+
+/// Reads every dependency of a package, including target-specific ones.
+fn package_dependencies(
+    package: &Package,
+    workspace_dependencies: &toml::Table,
+    root_directory: &Path,
+) -> Vec<Dependency> {
+    let mut tables = vec![&package.manifest];
+    if let Some(targets) = package
+        .manifest
+        .get("target")
+        .and_then(|value| value.as_table())
+    {
+        tables.extend(targets.values().filter_map(|target| target.as_table()));
+    }
+
+    let mut dependencies = vec![];
+    for table in tables {
+        for (table_name, kind) in DEPENDENCY_TABLES {
+            let Some(entries) = table.get(table_name).and_then(|value| value.as_table()) else {
+                continue;
+            };
+            for (key, specification) in entries {
+                dependencies.push(read_dependency(
+                    key,
+                    specification,
+                    kind,
+                    &package.directory,
+                    root_directory,
+                    workspace_dependencies,
+                ));
+            }
+        }
+    }
+
+    dependencies
+}
+
+/// Reads a dependency like `serde = "1"`, `foo = { path = "../foo", optional = true }`
+/// or `bar = { workspace = true }`.
+fn read_dependency(
+    key: &str,
+    specification: &toml::Value,
+    kind: EdgeKind,
+    package_directory: &Path,
+    root_directory: &Path,
+    workspace_dependencies: &toml::Table,
+) -> Dependency {
+    let optional = specification
+        .get("optional")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    let inherited = specification
+        .get("workspace")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    // Paths are relative to the manifest they're written in
+    let (specification, manifest_directory) = match workspace_dependencies.get(key) {
+        Some(workspace_specification) if inherited => (workspace_specification, root_directory),
+        _ => (specification, package_directory),
+    };
+
+    let name = specification
+        .get("package")
+        .and_then(|value| value.as_str())
+        .unwrap_or(key)
+        .to_string();
+    let directory = specification
+        .get("path")
+        .and_then(|value| value.as_str())
+        .map(|path| normalize_path(&manifest_directory.join(path)));
+
+    Dependency {
+        name,
+        kind: match kind {
+            EdgeKind::Normal if optional => EdgeKind::Optional,
+            kind => kind,
+        },
+        directory,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_generate_crate_graph() {
+        let base = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        let root = base.join("workspace");
+        write(
+            &root,
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["crates/*"]
+exclude = ["crates/ignored"]
+
+[workspace.dependencies]
+serde = "1"
+core = { path = "crates/core" }
+"#,
+        );
+        write(
+            &root,
+            "crates/app/Cargo.toml",
+            r#"
+[package]
+name = "app"
+
+[dependencies]
+core = { workspace = true }
+vendored = { path = "../../vendor/vendored" }
+shared = { path = "../../../shared" }
+missing = { path = "../missing" }
+fancy = { version = "0.1", optional = true }
+json = { package = "serde_json", version = "1" }
+
+[dev-dependencies]
+serde = { workspace = true }
+
+[target.'cfg(unix)'.build-dependencies]
+cc = "1"
+
+[target.'cfg(windows)'.build-dependencies]
+cc = "1"
+"#,
+        );
+        write(
+            &root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"app-core\"\n\n[dependencies]\nserde = { workspace = true }\n",
+        );
+        write(
+            &root,
+            "crates/ignored/Cargo.toml",
+            "[package]\nname = \"ignored\"\n",
+        );
+        write(
+            &root,
+            "vendor/vendored/Cargo.toml",
+            "[package]\nname = \"vendored\"\n",
+        );
+        write(&base, "shared/Cargo.toml", "[package]\nname = \"shared\"\n");
+
+        let config = Config {
+            title: "Workspace".to_string(),
+            language: "rust".to_string(),
+            path: root.to_str().unwrap().to_string(),
+            module_resolution: None,
+            groups: None,
+            extensions: None,
+            include: None,
+            exclude: None,
+            source_roots: None,
+            include_directories: None,
+            compile_commands: None,
+            packages: false,
+        };
+        let graph = generate_crate_graph(&config).unwrap();
+
+        let label = |id: &str| {
+            let node = graph.nodes.iter().find(|node| node.id == id).unwrap();
+            node.label.clone()
+        };
+        let mut edges: Vec<(String, String, EdgeKind)> = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                (
+                    label(&edge.source_id),
+                    label(&edge.target_id),
+                    edge.kind.unwrap(),
+                )
+            })
+            .collect();
+        edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        assert_eq!(
+            edges,
+            vec![
+                ("app".to_string(), "app-core".to_string(), EdgeKind::Normal),
+                ("app".to_string(), "cc".to_string(), EdgeKind::Build),
+                ("app".to_string(), "fancy".to_string(), EdgeKind::Optional),
+                ("app".to_string(), "missing".to_string(), EdgeKind::Normal),
+                ("app".to_string(), "serde".to_string(), EdgeKind::Dev),
+                (
+                    "app".to_string(),
+                    "serde_json".to_string(),
+                    EdgeKind::Normal
+                ),
+                ("app".to_string(), "shared".to_string(), EdgeKind::Normal),
+                ("app".to_string(), "vendored".to_string(), EdgeKind::Normal),
+                (
+                    "app-core".to_string(),
+                    "serde".to_string(),
+                    EdgeKind::Normal
+                ),
+            ]
+        );
+
+        let find_node = |label: &str| graph.nodes.iter().find(|node| node.label == label).unwrap();
+        let vendored = find_node("vendored");
+        assert!(matches!(vendored.node_type, NodeType::Internal));
+        assert_eq!(vendored.path_relative, "vendor/vendored");
+        let shared = find_node("shared");
+        assert!(matches!(shared.node_type, NodeType::Internal));
+        assert_eq!(shared.path_relative, "../shared");
+        assert!(matches!(find_node("missing").node_type, NodeType::External));
+        assert!(!graph.nodes.iter().any(|node| node.label == "ignored"));
+
+        let config = Config {
+            language: "typescript".to_string(),
+            ..config
+        };
+        let error = generate_crate_graph(&config).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Crate graphs need `language: rust` in the config, not \"typescript\""
+        );
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
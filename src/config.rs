@@ -3,7 +3,7 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub title: String,
     pub language: String,
@@ -11,14 +11,48 @@ pub struct Config {
 
     #[serde(rename = "moduleResolution")]
     pub module_resolution: Option<Vec<ModuleResolutionItem>>,
+
+    pub groups: Option<Vec<GroupItem>>,
+
+    /// Extensions of the source files to analyze, overriding the language's defaults
+    pub extensions: Option<Vec<String>>,
+
+    /// Glob patterns for the files to analyze, relative to `path`; all source files by default
+    pub include: Option<Vec<String>>,
+
+    /// Glob patterns for files to leave out, relative to `path`
+    pub exclude: Option<Vec<String>>,
+
+    /// Directories absolute imports are resolved from, relative to `path` (Python, Java and Kotlin)
+    #[serde(rename = "sourceRoots")]
+    pub source_roots: Option<Vec<String>>,
+
+    /// Directories to look up `#include`s in, relative to `path`, like `-I` flags (C and C++)
+    #[serde(rename = "includeDirectories")]
+    pub include_directories: Option<Vec<String>>,
+
+    /// Compilation database to read more include directories from, relative to
+    /// `path`; `compile_commands.json` is read by default if it exists (C and C++)
+    #[serde(rename = "compileCommands")]
+    pub compile_commands: Option<String>,
+
+    /// Draw one node per package instead of per source file (Java and Kotlin)
+    #[serde(default)]
+    pub packages: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ModuleResolutionItem {
     pub pattern: String,
     pub replacement: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct GroupItem {
+    pub name: String,
+    pub pattern: String,
+}
+
 pub fn read_config_file(file_path: &str) -> Result<Config, Box<dyn std::error::Error>> {
     let path = Path::new(file_path);
     let mut file = File::open(path)?;
@@ -0,0 +1,464 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::graph::{Graph, Node, NodeType};
+use crate::metrics::{find_cycles, Adjacency};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum DiffFormat {
+    /// Plain text for the terminal
+    Text,
+    /// JSON for scripts
+    Json,
+    /// Markdown, for posting as a pull request comment
+    Markdown,
+}
+
+/// Differences between two graphs.
+///
+/// Modules are identified by their relative path, and external modules by
+/// their name, so the node ids of the two graphs don't need to match.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub added_edges: Vec<EdgeKey>,
+    pub removed_edges: Vec<EdgeKey>,
+    pub new_cycles: Vec<Vec<String>>,
+    pub added_external_packages: Vec<String>,
+    pub removed_external_packages: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EdgeKey {
+    pub source: String,
+    pub target: String,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == GraphDiff::default()
+    }
+}
+
+pub fn diff_graphs(old: &Graph, new: &Graph) -> GraphDiff {
+    let old_nodes = node_keys(old);
+    let new_nodes = node_keys(new);
+    let old_edges = edge_keys(old);
+    let new_edges = edge_keys(new);
+    let old_packages = external_packages(old);
+    let new_packages = external_packages(new);
+    let old_cycles = cycles(old);
+
+    GraphDiff {
+        added_nodes: new_nodes.difference(&old_nodes).cloned().collect(),
+        removed_nodes: old_nodes.difference(&new_nodes).cloned().collect(),
+        added_edges: new_edges.difference(&old_edges).cloned().collect(),
+        removed_edges: old_edges.difference(&new_edges).cloned().collect(),
+        new_cycles: cycles(new)
+            .into_iter()
+            .filter(|cycle| !old_cycles.contains(cycle))
+            .collect(),
+        added_external_packages: new_packages.difference(&old_packages).cloned().collect(),
+        removed_external_packages: old_packages.difference(&new_packages).cloned().collect(),
+    }
+}
+
+fn node_key(node: &Node) -> String {
+    match node.node_type {
+        NodeType::External | NodeType::Stdlib => node.label.clone(),
+        _ if node.path_relative.is_empty() => node.label.clone(),
+        _ => node.path_relative.clone(),
+    }
+}
+
+fn node_keys(graph: &Graph) -> BTreeSet<String> {
+    graph.nodes.iter().map(node_key).collect()
+}
+
+fn edge_keys(graph: &Graph) -> BTreeSet<EdgeKey> {
+    let keys_by_id: HashMap<&str, String> = graph
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node_key(node)))
+        .collect();
+
+    graph
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            let source = keys_by_id.get(edge.source_id.as_str())?;
+            let target = keys_by_id.get(edge.target_id.as_str())?;
+            Some(EdgeKey {
+                source: source.clone(),
+                target: target.clone(),
+            })
+        })
+        .collect()
+}
+
+fn cycles(graph: &Graph) -> BTreeSet<Vec<String>> {
+    find_cycles(&Adjacency::new(graph))
+        .into_iter()
+        .map(|component| {
+            let mut keys: Vec<String> = component
+                .into_iter()
+                .map(|index| node_key(&graph.nodes[index]))
+                .collect();
+            keys.sort();
+            keys
+        })
+        .collect()
+}
+
+pub fn external_packages(graph: &Graph) -> BTreeSet<String> {
+    graph
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.node_type, NodeType::External))
+        // Relative imports that couldn't be resolved aren't packages
+        .filter(|node| !node.label.starts_with('.'))
+        .map(|node| get_package_name(graph.language.as_deref(), &node.label))
+        .collect()
+}
+
+/// Returns the package an external module belongs to, e.g. `lodash` for
+/// `lodash/fp` in JavaScript. Graphs without a language come from versions
+/// that only read JavaScript and TypeScript.
+pub fn get_package_name(language: Option<&str>, module_name: &str) -> String {
+    let is_javascript = language.is_none_or(|language| {
+        matches!(
+            language.to_lowercase().as_str(),
+            "typescript" | "javascript"
+        )
+    });
+    if !is_javascript {
+        // The other languages already make one node per package, like a Go
+        // module or a Java package, or per header in C
+        return module_name.to_string();
+    }
+
+    let segments: Vec<&str> = module_name.split('/').collect();
+    if module_name.starts_with('@') && segments.len() > 1 {
+        format!("{}/{}", segments[0], segments[1])
+    } else {
+        segments[0].to_string()
+    }
+}
+
+pub fn render_diff(diff: &GraphDiff, format: DiffFormat) -> String {
+    match format {
+        DiffFormat::Text => render_text(diff),
+        DiffFormat::Json => serde_json::to_string_pretty(diff).unwrap() + "\n",
+        DiffFormat::Markdown => render_markdown(diff),
+    }
+}
+
+fn render_text(diff: &GraphDiff) -> String {
+    let mut output = String::new();
+
+    if diff.is_empty() {
+        writeln!(output, "No dependency changes.").unwrap();
+        return output;
+    }
+
+    let sections: [(&str, Vec<String>); 7] = [
+        ("Added modules", diff.added_nodes.clone()),
+        ("Removed modules", diff.removed_nodes.clone()),
+        ("Added imports", format_edges(&diff.added_edges, " -> ")),
+        ("Removed imports", format_edges(&diff.removed_edges, " -> ")),
+        ("New import cycles", format_cycles(&diff.new_cycles, ", ")),
+        (
+            "New external packages",
+            diff.added_external_packages.clone(),
+        ),
+        (
+            "Removed external packages",
+            diff.removed_external_packages.clone(),
+        ),
+    ];
+
+    for (title, lines) in sections.iter() {
+        if lines.is_empty() {
+            continue;
+        }
+        writeln!(output, "{} ({}):", title, lines.len()).unwrap();
+        for line in lines.iter() {
+            writeln!(output, "  {}", line).unwrap();
+        }
+        writeln!(output).unwrap();
+    }
+
+    output
+}
+
+fn render_markdown(diff: &GraphDiff) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "## Dependency changes").unwrap();
+    writeln!(output).unwrap();
+
+    if diff.is_empty() {
+        writeln!(output, "No dependency changes.").unwrap();
+        return output;
+    }
+
+    writeln!(output, "| | Added | Removed |").unwrap();
+    writeln!(output, "| --- | ---: | ---: |").unwrap();
+    writeln!(
+        output,
+        "| Modules | {} | {} |",
+        diff.added_nodes.len(),
+        diff.removed_nodes.len()
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "| Imports | {} | {} |",
+        diff.added_edges.len(),
+        diff.removed_edges.len()
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "| External packages | {} | {} |",
+        diff.added_external_packages.len(),
+        diff.removed_external_packages.len()
+    )
+    .unwrap();
+    writeln!(output).unwrap();
+
+    if !diff.new_cycles.is_empty() {
+        writeln!(
+            output,
+            "> **Warning:** this change introduces {} new import cycle(s).",
+            diff.new_cycles.len()
+        )
+        .unwrap();
+        writeln!(output).unwrap();
+    }
+
+    let sections: [(&str, Vec<String>); 7] = [
+        (
+            "New external packages",
+            diff.added_external_packages.clone(),
+        ),
+        (
+            "Removed external packages",
+            diff.removed_external_packages.clone(),
+        ),
+        ("New import cycles", format_cycles(&diff.new_cycles, "`, `")),
+        ("Added modules", diff.added_nodes.clone()),
+        ("Removed modules", diff.removed_nodes.clone()),
+        ("Added imports", format_edges(&diff.added_edges, "` → `")),
+        (
+            "Removed imports",
+            format_edges(&diff.removed_edges, "` → `"),
+        ),
+    ];
+
+    for (title, lines) in sections.iter() {
+        if lines.is_empty() {
+            continue;
+        }
+        writeln!(output, "<details>").unwrap();
+        writeln!(output, "<summary>{} ({})</summary>", title, lines.len()).unwrap();
+        writeln!(output).unwrap();
+        for line in lines.iter() {
+            writeln!(output, "- `{}`", line).unwrap();
+        }
+        writeln!(output).unwrap();
+        writeln!(output, "</details>").unwrap();
+        writeln!(output).unwrap();
+    }
+
+    output
+}
+
+fn format_edges(edges: &[EdgeKey], separator: &str) -> Vec<String> {
+    edges
+        .iter()
+        .map(|edge| format!("{}{}{}", edge.source, separator, edge.target))
+        .collect()
+}
+
+fn format_cycles(cycles: &[Vec<String>], separator: &str) -> Vec<String> {
+    cycles.iter().map(|cycle| cycle.join(separator)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, SCHEMA_VERSION};
+
+    fn test_graph(nodes: &[(&str, NodeType)], edges: &[(&str, &str)]) -> Graph {
+        // Give each graph its own ids, like two separate runs would
+        let id_prefix = uuid::Uuid::new_v4().to_string();
+        let id = |name: &str| format!("{}-{}", id_prefix, name);
+
+        Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: nodes
+                .iter()
+                .map(|(name, node_type)| {
+                    let path_relative = match node_type {
+                        NodeType::External => "".to_string(),
+                        _ => name.to_string(),
+                    };
+                    Node {
+                        id: id(name),
+                        node_type: node_type.clone(),
+                        label: name.to_string(),
+                        path_absolute: "".to_string(),
+                        path_relative,
+                        metrics: None,
+                        size: None,
+                    }
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(source, target)| Edge {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    source_id: id(source),
+                    target_id: id(target),
+                    weight: None,
+                    kind: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn edge_key(source: &str, target: &str) -> EdgeKey {
+        EdgeKey {
+            source: source.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_get_package_name() {
+        let typescript = Some("typescript");
+        assert_eq!(get_package_name(typescript, "react"), "react");
+        assert_eq!(get_package_name(typescript, "lodash/fp"), "lodash");
+        assert_eq!(
+            get_package_name(typescript, "@emotion/react"),
+            "@emotion/react"
+        );
+        assert_eq!(
+            get_package_name(typescript, "@mui/material/Button"),
+            "@mui/material"
+        );
+        assert_eq!(get_package_name(None, "lodash/fp"), "lodash");
+
+        assert_eq!(
+            get_package_name(Some("go"), "github.com/pkg/errors"),
+            "github.com/pkg/errors"
+        );
+        assert_eq!(
+            get_package_name(Some("go"), "golang.org/x/sync"),
+            "golang.org/x/sync"
+        );
+        assert_eq!(get_package_name(Some("c"), "sys/types.h"), "sys/types.h");
+        assert_eq!(
+            get_package_name(Some("cpp"), "boost/asio.hpp"),
+            "boost/asio.hpp"
+        );
+    }
+
+    #[test]
+    fn test_diff_graphs() {
+        let old = test_graph(
+            &[
+                ("a.ts", NodeType::Internal),
+                ("b.ts", NodeType::Internal),
+                ("c.ts", NodeType::Internal),
+                ("lodash/fp", NodeType::External),
+            ],
+            &[("a.ts", "b.ts"), ("b.ts", "c.ts"), ("c.ts", "lodash/fp")],
+        );
+        let new = test_graph(
+            &[
+                ("a.ts", NodeType::Internal),
+                ("b.ts", NodeType::Internal),
+                ("d.ts", NodeType::Internal),
+                ("lodash", NodeType::External),
+                ("react", NodeType::External),
+            ],
+            &[
+                ("a.ts", "b.ts"),
+                ("b.ts", "a.ts"),
+                ("b.ts", "d.ts"),
+                ("d.ts", "lodash"),
+                ("d.ts", "react"),
+            ],
+        );
+
+        let diff = diff_graphs(&old, &new);
+
+        assert_eq!(diff.added_nodes, vec!["d.ts", "lodash", "react"]);
+        assert_eq!(diff.removed_nodes, vec!["c.ts", "lodash/fp"]);
+        assert_eq!(
+            diff.added_edges,
+            vec![
+                edge_key("b.ts", "a.ts"),
+                edge_key("b.ts", "d.ts"),
+                edge_key("d.ts", "lodash"),
+                edge_key("d.ts", "react"),
+            ]
+        );
+        assert_eq!(
+            diff.removed_edges,
+            vec![edge_key("b.ts", "c.ts"), edge_key("c.ts", "lodash/fp")]
+        );
+        assert_eq!(diff.new_cycles, vec![vec!["a.ts", "b.ts"]]);
+        assert_eq!(diff.added_external_packages, vec!["react"]);
+        assert!(diff.removed_external_packages.is_empty());
+    }
+
+    #[test]
+    fn test_diff_identical_graphs() {
+        let nodes = [("a.ts", NodeType::Internal), ("b.ts", NodeType::Internal)];
+        let edges = [("a.ts", "b.ts"), ("b.ts", "a.ts")];
+        let diff = diff_graphs(&test_graph(&nodes, &edges), &test_graph(&nodes, &edges));
+
+        assert!(diff.is_empty());
+        assert_eq!(
+            render_diff(&diff, DiffFormat::Text),
+            "No dependency changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_render_diff_text() {
+        let diff = GraphDiff {
+            added_edges: vec![edge_key("a.ts", "b.ts")],
+            new_cycles: vec![vec!["a.ts".to_string(), "b.ts".to_string()]],
+            ..GraphDiff::default()
+        };
+
+        assert_eq!(
+            render_diff(&diff, DiffFormat::Text),
+            "Added imports (1):\n  a.ts -> b.ts\n\nNew import cycles (1):\n  a.ts, b.ts\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_diff_markdown() {
+        let diff = GraphDiff {
+            added_external_packages: vec!["react".to_string()],
+            ..GraphDiff::default()
+        };
+
+        let output = render_diff(&diff, DiffFormat::Markdown);
+
+        assert!(output.contains("| External packages | 1 | 0 |\n"));
+        assert!(output
+            .contains("<summary>New external packages (1)</summary>\n\n- `react`\n\n</details>\n"));
+    }
+}
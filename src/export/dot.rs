@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::graph::{EdgeKind, Graph, Node, NodeType};
+
+pub fn render_dot(graph: &Graph, cluster: bool) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "digraph {} {{", quote(&graph.title)).unwrap();
+    writeln!(output, "  rankdir=LR;").unwrap();
+    writeln!(output, "  node [fontname=\"Helvetica\"];").unwrap();
+    writeln!(output).unwrap();
+
+    if cluster {
+        let mut nodes_by_directory: BTreeMap<String, Vec<&Node>> = BTreeMap::new();
+        for node in graph.nodes.iter() {
+            if node.node_type.is_internal() {
+                nodes_by_directory
+                    .entry(get_directory(&node.path_relative))
+                    .or_default()
+                    .push(node);
+            } else {
+                write_node(&mut output, node, "  ");
+            }
+        }
+
+        for (index, (directory, nodes)) in nodes_by_directory.iter().enumerate() {
+            writeln!(output, "  subgraph cluster_{} {{", index).unwrap();
+            writeln!(output, "    label={};", quote(directory)).unwrap();
+            for node in nodes.iter() {
+                write_node(&mut output, node, "    ");
+            }
+            writeln!(output, "  }}").unwrap();
+        }
+    } else {
+        for node in graph.nodes.iter() {
+            write_node(&mut output, node, "  ");
+        }
+    }
+
+    writeln!(output).unwrap();
+
+    for edge in graph.edges.iter() {
+        write!(
+            output,
+            "  {} -> {}",
+            quote(&edge.source_id),
+            quote(&edge.target_id)
+        )
+        .unwrap();
+
+        let mut attributes = vec![];
+        if let Some(weight) = edge.weight {
+            attributes.push(format!("label=\"{}\", weight={}", weight, weight));
+        }
+        match edge.kind {
+            Some(EdgeKind::Optional) => attributes.push("color=gray50".to_string()),
+            Some(EdgeKind::Dev) => attributes.push("style=dashed".to_string()),
+            Some(EdgeKind::Build) => attributes.push("style=dotted".to_string()),
+            Some(EdgeKind::Normal) | None => {}
+        }
+        if !attributes.is_empty() {
+            write!(output, " [{}]", attributes.join(", ")).unwrap();
+        }
+        writeln!(output, ";").unwrap();
+    }
+
+    writeln!(output, "}}").unwrap();
+
+    output
+}
+
+fn write_node(output: &mut String, node: &Node, indent: &str) {
+    let attributes = match node.node_type {
+        NodeType::Internal => "shape=box",
+        NodeType::External => "shape=ellipse, style=dashed",
+        NodeType::Stdlib => "shape=ellipse, style=dotted",
+        NodeType::Group => "shape=folder",
+        NodeType::Stylesheet => "shape=note",
+        NodeType::Asset => "shape=tab",
+    };
+
+    writeln!(
+        output,
+        "{}{} [label={}, {}];",
+        indent,
+        quote(&node.id),
+        quote(&node.label),
+        attributes
+    )
+    .unwrap();
+}
+
+fn get_directory(path_relative: &str) -> String {
+    match Path::new(path_relative).parent() {
+        Some(parent) if parent.as_os_str().is_empty() => ".".to_string(),
+        Some(parent) => parent.to_str().unwrap().to_string(),
+        None => ".".to_string(),
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, SCHEMA_VERSION};
+
+    fn test_graph() -> Graph {
+        let node = |id: &str, node_type: NodeType, path_relative: &str| Node {
+            id: id.to_string(),
+            node_type,
+            label: id.to_string(),
+            path_absolute: "".to_string(),
+            path_relative: path_relative.to_string(),
+            metrics: None,
+            size: None,
+        };
+
+        Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "My \"App\"".to_string(),
+            language: None,
+            nodes: vec![
+                node("a", NodeType::Internal, "src/a.ts"),
+                node("b", NodeType::Internal, "src/lib/b.ts"),
+                node("react", NodeType::External, ""),
+            ],
+            edges: vec![
+                Edge {
+                    id: "1".to_string(),
+                    source_id: "a".to_string(),
+                    target_id: "b".to_string(),
+                    weight: None,
+                    kind: None,
+                },
+                Edge {
+                    id: "2".to_string(),
+                    source_id: "a".to_string(),
+                    target_id: "react".to_string(),
+                    weight: Some(3),
+                    kind: None,
+                },
+                Edge {
+                    id: "3".to_string(),
+                    source_id: "b".to_string(),
+                    target_id: "react".to_string(),
+                    weight: None,
+                    kind: Some(EdgeKind::Dev),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let output = render_dot(&test_graph(), false);
+
+        assert!(output.starts_with("digraph \"My \\\"App\\\"\" {\n"));
+        assert!(output.contains("  \"a\" [label=\"a\", shape=box];\n"));
+        assert!(output.contains("  \"react\" [label=\"react\", shape=ellipse, style=dashed];\n"));
+        assert!(output.contains("  \"a\" -> \"b\";\n"));
+        assert!(output.contains("  \"a\" -> \"react\" [label=\"3\", weight=3];\n"));
+        assert!(output.contains("  \"b\" -> \"react\" [style=dashed];\n"));
+        assert!(!output.contains("subgraph"));
+    }
+
+    #[test]
+    fn test_render_dot_clustered() {
+        let output = render_dot(&test_graph(), true);
+
+        assert!(output.contains(
+            "  subgraph cluster_0 {\n    label=\"src\";\n    \"a\" [label=\"a\", shape=box];\n  }\n"
+        ));
+        assert!(output.contains(
+            "  subgraph cluster_1 {\n    label=\"src/lib\";\n    \"b\" [label=\"b\", shape=box];\n  }\n"
+        ));
+        assert!(output.contains("  \"react\" [label=\"react\", shape=ellipse, style=dashed];\n"));
+    }
+}
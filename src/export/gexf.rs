@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+use super::{edge_kind_name, escape_xml, node_attribute_values, AttributeType, NODE_ATTRIBUTES};
+use crate::graph::Graph;
+
+pub fn render_gexf(graph: &Graph) -> String {
+    let mut output = String::new();
+
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        output,
+        r#"<gexf xmlns="http://gexf.net/1.3" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://gexf.net/1.3 http://gexf.net/1.3/gexf.xsd" version="1.3">"#
+    )
+    .unwrap();
+    writeln!(output, "  <meta>").unwrap();
+    writeln!(output, "    <creator>depdoc</creator>").unwrap();
+    writeln!(
+        output,
+        "    <description>{}</description>",
+        escape_xml(&graph.title)
+    )
+    .unwrap();
+    writeln!(output, "  </meta>").unwrap();
+    writeln!(
+        output,
+        r#"  <graph mode="static" defaultedgetype="directed">"#
+    )
+    .unwrap();
+
+    writeln!(output, r#"    <attributes class="node">"#).unwrap();
+    for (name, attribute_type) in NODE_ATTRIBUTES.iter() {
+        writeln!(
+            output,
+            r#"      <attribute id="{}" title="{}" type="{}"/>"#,
+            name,
+            name,
+            type_name(*attribute_type)
+        )
+        .unwrap();
+    }
+    writeln!(output, "    </attributes>").unwrap();
+    writeln!(output, r#"    <attributes class="edge">"#).unwrap();
+    writeln!(
+        output,
+        r#"      <attribute id="kind" title="kind" type="string"/>"#
+    )
+    .unwrap();
+    writeln!(output, "    </attributes>").unwrap();
+
+    writeln!(output, "    <nodes>").unwrap();
+    for node in graph.nodes.iter() {
+        writeln!(
+            output,
+            r#"      <node id="{}" label="{}">"#,
+            escape_xml(&node.id),
+            escape_xml(&node.label)
+        )
+        .unwrap();
+        writeln!(output, "        <attvalues>").unwrap();
+        for (name, value) in node_attribute_values(node) {
+            writeln!(
+                output,
+                r#"          <attvalue for="{}" value="{}"/>"#,
+                name,
+                escape_xml(&value)
+            )
+            .unwrap();
+        }
+        writeln!(output, "        </attvalues>").unwrap();
+        writeln!(output, "      </node>").unwrap();
+    }
+    writeln!(output, "    </nodes>").unwrap();
+
+    writeln!(output, "    <edges>").unwrap();
+    for edge in graph.edges.iter() {
+        write!(
+            output,
+            r#"      <edge id="{}" source="{}" target="{}" weight="{}""#,
+            escape_xml(&edge.id),
+            escape_xml(&edge.source_id),
+            escape_xml(&edge.target_id),
+            edge.weight.unwrap_or(1)
+        )
+        .unwrap();
+        match edge.kind {
+            Some(kind) => {
+                writeln!(output, ">").unwrap();
+                writeln!(output, "        <attvalues>").unwrap();
+                writeln!(
+                    output,
+                    r#"          <attvalue for="kind" value="{}"/>"#,
+                    edge_kind_name(kind)
+                )
+                .unwrap();
+                writeln!(output, "        </attvalues>").unwrap();
+                writeln!(output, "      </edge>").unwrap();
+            }
+            None => writeln!(output, "/>").unwrap(),
+        }
+    }
+    writeln!(output, "    </edges>").unwrap();
+
+    writeln!(output, "  </graph>").unwrap();
+    writeln!(output, "</gexf>").unwrap();
+
+    output
+}
+
+fn type_name(attribute_type: AttributeType) -> &'static str {
+    match attribute_type {
+        AttributeType::String => "string",
+        AttributeType::Integer => "integer",
+        AttributeType::Double => "double",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, EdgeKind, Node, NodeType, SCHEMA_VERSION};
+
+    #[test]
+    fn test_render_gexf() {
+        let graph = Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "<My App>".to_string(),
+            language: None,
+            nodes: vec![
+                Node {
+                    id: "a".to_string(),
+                    node_type: NodeType::Internal,
+                    label: "a.ts".to_string(),
+                    path_absolute: "/repo/a.ts".to_string(),
+                    path_relative: "a.ts".to_string(),
+                    metrics: None,
+                    size: None,
+                },
+                Node {
+                    id: "b".to_string(),
+                    node_type: NodeType::External,
+                    label: "react".to_string(),
+                    path_absolute: "".to_string(),
+                    path_relative: "".to_string(),
+                    metrics: None,
+                    size: None,
+                },
+            ],
+            edges: vec![
+                Edge {
+                    id: "e".to_string(),
+                    source_id: "a".to_string(),
+                    target_id: "b".to_string(),
+                    weight: None,
+                    kind: None,
+                },
+                Edge {
+                    id: "f".to_string(),
+                    source_id: "a".to_string(),
+                    target_id: "b".to_string(),
+                    weight: None,
+                    kind: Some(EdgeKind::Build),
+                },
+            ],
+        };
+
+        let output = render_gexf(&graph);
+
+        assert!(output.contains("    <description>&lt;My App&gt;</description>\n"));
+        assert!(output.contains(
+            r#"      <attribute id="afferent_coupling" title="afferent_coupling" type="integer"/>"#
+        ));
+        assert!(output.contains(
+            r#"      <node id="b" label="react">
+        <attvalues>
+          <attvalue for="node_type" value="EXTERNAL"/>
+          <attvalue for="path_relative" value=""/>
+          <attvalue for="path_absolute" value=""/>
+        </attvalues>
+      </node>
+"#
+        ));
+        assert!(output.contains(r#"      <edge id="e" source="a" target="b" weight="1"/>"#));
+        assert!(output.contains(
+            r#"      <edge id="f" source="a" target="b" weight="1">
+        <attvalues>
+          <attvalue for="kind" value="BUILD"/>
+        </attvalues>
+      </edge>
+"#
+        ));
+    }
+}
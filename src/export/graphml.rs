@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use super::{edge_kind_name, escape_xml, node_attribute_values, AttributeType, NODE_ATTRIBUTES};
+use crate::graph::Graph;
+
+pub fn render_graphml(graph: &Graph) -> String {
+    let mut output = String::new();
+
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        output,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+    )
+    .unwrap();
+
+    writeln!(
+        output,
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+    )
+    .unwrap();
+    for (name, attribute_type) in NODE_ATTRIBUTES.iter() {
+        writeln!(
+            output,
+            r#"  <key id="{}" for="node" attr.name="{}" attr.type="{}"/>"#,
+            name,
+            name,
+            type_name(*attribute_type)
+        )
+        .unwrap();
+    }
+    writeln!(
+        output,
+        r#"  <key id="weight" for="edge" attr.name="weight" attr.type="int"><default>1</default></key>"#
+    )
+    .unwrap();
+    writeln!(
+        output,
+        r#"  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>"#
+    )
+    .unwrap();
+
+    writeln!(output, r#"  <graph id="G" edgedefault="directed">"#).unwrap();
+    writeln!(output, "    <desc>{}</desc>", escape_xml(&graph.title)).unwrap();
+
+    for node in graph.nodes.iter() {
+        writeln!(output, r#"    <node id="{}">"#, escape_xml(&node.id)).unwrap();
+        writeln!(
+            output,
+            r#"      <data key="label">{}</data>"#,
+            escape_xml(&node.label)
+        )
+        .unwrap();
+        for (name, value) in node_attribute_values(node) {
+            writeln!(
+                output,
+                r#"      <data key="{}">{}</data>"#,
+                name,
+                escape_xml(&value)
+            )
+            .unwrap();
+        }
+        writeln!(output, "    </node>").unwrap();
+    }
+
+    for edge in graph.edges.iter() {
+        write!(
+            output,
+            r#"    <edge id="{}" source="{}" target="{}""#,
+            escape_xml(&edge.id),
+            escape_xml(&edge.source_id),
+            escape_xml(&edge.target_id)
+        )
+        .unwrap();
+
+        let mut data = String::new();
+        if let Some(weight) = edge.weight {
+            write!(data, r#"<data key="weight">{}</data>"#, weight).unwrap();
+        }
+        if let Some(kind) = edge.kind {
+            write!(data, r#"<data key="kind">{}</data>"#, edge_kind_name(kind)).unwrap();
+        }
+        if data.is_empty() {
+            writeln!(output, "/>").unwrap();
+        } else {
+            writeln!(output, ">{}</edge>", data).unwrap();
+        }
+    }
+
+    writeln!(output, "  </graph>").unwrap();
+    writeln!(output, "</graphml>").unwrap();
+
+    output
+}
+
+fn type_name(attribute_type: AttributeType) -> &'static str {
+    match attribute_type {
+        AttributeType::String => "string",
+        AttributeType::Integer => "int",
+        AttributeType::Double => "double",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, EdgeKind, Node, NodeType, SCHEMA_VERSION};
+    use crate::metrics::attach_metrics;
+
+    #[test]
+    fn test_render_graphml() {
+        let node = |id: &str, label: &str| Node {
+            id: id.to_string(),
+            node_type: NodeType::Internal,
+            label: label.to_string(),
+            path_absolute: format!("/repo/{}", label),
+            path_relative: label.to_string(),
+            metrics: None,
+            size: None,
+        };
+        let mut graph = Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: vec![node("a", "A&B.tsx"), node("b", "b.ts"), node("c", "c.ts")],
+            edges: vec![
+                Edge {
+                    id: "e".to_string(),
+                    source_id: "a".to_string(),
+                    target_id: "b".to_string(),
+                    weight: Some(2),
+                    kind: None,
+                },
+                Edge {
+                    id: "f".to_string(),
+                    source_id: "b".to_string(),
+                    target_id: "c".to_string(),
+                    weight: None,
+                    kind: Some(EdgeKind::Dev),
+                },
+            ],
+        };
+        attach_metrics(&mut graph);
+
+        let output = render_graphml(&graph);
+
+        assert!(output.contains(
+            r#"<key id="instability" for="node" attr.name="instability" attr.type="double"/>"#
+        ));
+        assert!(output.contains(
+            r#"    <node id="a">
+      <data key="label">A&amp;B.tsx</data>
+      <data key="node_type">INTERNAL</data>
+      <data key="path_relative">A&amp;B.tsx</data>
+      <data key="path_absolute">/repo/A&amp;B.tsx</data>
+      <data key="afferent_coupling">0</data>
+      <data key="efferent_coupling">1</data>
+      <data key="instability">1</data>
+"#
+        ));
+        assert!(output.contains(
+            r#"    <edge id="e" source="a" target="b"><data key="weight">2</data></edge>"#
+        ));
+        assert!(output.contains(
+            r#"    <edge id="f" source="b" target="c"><data key="kind">DEV</data></edge>"#
+        ));
+        assert!(output.ends_with("  </graph>\n</graphml>\n"));
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::graph::{EdgeKind, Graph, Node, NodeType};
+
+pub fn render_mermaid(graph: &Graph) -> String {
+    let identifiers = create_identifiers(&graph.nodes);
+    let mut output = String::new();
+
+    writeln!(output, "flowchart LR").unwrap();
+
+    let mut nodes: Vec<&Node> = graph.nodes.iter().collect();
+    nodes.sort_by_key(|node| &identifiers[node.id.as_str()]);
+    for node in nodes {
+        let identifier = &identifiers[node.id.as_str()];
+        let label = escape_label(&node.label);
+        let shape = match node.node_type {
+            NodeType::Internal => format!("[\"{}\"]", label),
+            NodeType::External | NodeType::Stdlib => format!("([\"{}\"])", label),
+            NodeType::Group => format!("[[\"{}\"]]", label),
+            NodeType::Stylesheet => format!("[/\"{}\"/]", label),
+            NodeType::Asset => format!("{{{{\"{}\"}}}}", label),
+        };
+        writeln!(output, "  {}{}", identifier, shape).unwrap();
+    }
+
+    let mut edges: Vec<(&String, &String, Option<&str>, Option<usize>)> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                &identifiers[edge.source_id.as_str()],
+                &identifiers[edge.target_id.as_str()],
+                edge.kind.and_then(edge_kind_label),
+                edge.weight,
+            )
+        })
+        .collect();
+    edges.sort();
+    for (source, target, kind, weight) in edges {
+        // Edges of other kinds than a regular dependency are dotted and labeled with it
+        let arrow = if kind.is_some() { "-.->" } else { "-->" };
+        let label: Vec<String> = kind
+            .map(|kind| kind.to_string())
+            .into_iter()
+            .chain(weight.map(|weight| weight.to_string()))
+            .collect();
+        if label.is_empty() {
+            writeln!(output, "  {} {} {}", source, arrow, target).unwrap();
+        } else {
+            writeln!(
+                output,
+                "  {} {}|{}| {}",
+                source,
+                arrow,
+                label.join(" "),
+                target
+            )
+            .unwrap();
+        }
+    }
+
+    output
+}
+
+/// Derives a readable identifier for each node from its path or package name.
+///
+/// Unlike node ids, these don't change between runs, so the diagram source
+/// stays stable when it is checked in. Nodes and edges are sorted by these
+/// identifiers for the same reason.
+fn create_identifiers(nodes: &[Node]) -> HashMap<&str, String> {
+    let mut sorted_nodes: Vec<&Node> = nodes.iter().collect();
+    sorted_nodes.sort_by_key(|node| {
+        (
+            identifier_base(node),
+            node.path_relative.clone(),
+            node.label.clone(),
+        )
+    });
+
+    let mut used: HashSet<String> = HashSet::new();
+    let mut identifiers: HashMap<&str, String> = HashMap::new();
+    for node in sorted_nodes {
+        let base = identifier_base(node);
+        let mut identifier = base.clone();
+        let mut suffix = 2;
+        while used.contains(&identifier) {
+            identifier = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        used.insert(identifier.clone());
+        identifiers.insert(&node.id, identifier);
+    }
+
+    identifiers
+}
+
+fn identifier_base(node: &Node) -> String {
+    match node.node_type {
+        NodeType::Internal | NodeType::Stylesheet | NodeType::Asset => {
+            sanitize(&node.path_relative)
+        }
+        NodeType::External => format!("ext_{}", sanitize(&node.label)),
+        NodeType::Stdlib => format!("std_{}", sanitize(&node.label)),
+        NodeType::Group => format!("group_{}", sanitize(&node.label)),
+    }
+}
+
+fn sanitize(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    // "end" is a keyword in Mermaid flowcharts
+    if sanitized.is_empty() || sanitized == "end" {
+        format!("m_{}", sanitized)
+    } else {
+        sanitized
+    }
+}
+
+fn edge_kind_label(kind: EdgeKind) -> Option<&'static str> {
+    match kind {
+        EdgeKind::Normal => None,
+        EdgeKind::Optional => Some("optional"),
+        EdgeKind::Dev => Some("dev"),
+        EdgeKind::Build => Some("build"),
+    }
+}
+
+fn escape_label(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, SCHEMA_VERSION};
+
+    fn test_node(id: &str, node_type: NodeType, label: &str, path_relative: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            node_type,
+            label: label.to_string(),
+            path_absolute: "".to_string(),
+            path_relative: path_relative.to_string(),
+            metrics: None,
+            size: None,
+        }
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let graph = Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: vec![
+                test_node("1", NodeType::Internal, "src/index.ts", "src/index.ts"),
+                test_node("2", NodeType::Internal, "App.tsx", "src/components/App.tsx"),
+                test_node("3", NodeType::External, "@emotion/react", ""),
+            ],
+            edges: vec![
+                Edge {
+                    id: "a".to_string(),
+                    source_id: "1".to_string(),
+                    target_id: "2".to_string(),
+                    weight: None,
+                    kind: None,
+                },
+                Edge {
+                    id: "b".to_string(),
+                    source_id: "2".to_string(),
+                    target_id: "3".to_string(),
+                    weight: Some(2),
+                    kind: None,
+                },
+                Edge {
+                    id: "c".to_string(),
+                    source_id: "1".to_string(),
+                    target_id: "3".to_string(),
+                    weight: None,
+                    kind: Some(EdgeKind::Dev),
+                },
+            ],
+        };
+
+        let output = render_mermaid(&graph);
+
+        assert_eq!(
+            output,
+            r#"flowchart LR
+  ext__emotion_react(["@emotion/react"])
+  src_components_App_tsx["App.tsx"]
+  src_index_ts["src/index.ts"]
+  src_components_App_tsx -->|2| ext__emotion_react
+  src_index_ts -.->|dev| ext__emotion_react
+  src_index_ts --> src_components_App_tsx
+"#
+        );
+    }
+
+    #[test]
+    fn test_create_identifiers_deduplicates() {
+        let nodes = vec![
+            test_node("1", NodeType::Internal, "a-b.ts", "a-b.ts"),
+            test_node("2", NodeType::Internal, "a_b.ts", "a_b.ts"),
+            test_node("3", NodeType::Internal, "end", "end"),
+        ];
+
+        let identifiers = create_identifiers(&nodes);
+
+        assert_eq!(identifiers["1"], "a_b_ts");
+        assert_eq!(identifiers["2"], "a_b_ts_2");
+        assert_eq!(identifiers["3"], "m_end");
+    }
+}
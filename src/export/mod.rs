@@ -0,0 +1,142 @@
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, Write};
+
+use crate::graph::{EdgeKind, Graph, Node};
+
+mod dot;
+mod gexf;
+mod graphml;
+mod mermaid;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// JSON for the interactive viewer
+    Json,
+    /// Graphviz DOT, for rendering with `dot`
+    Dot,
+    /// Mermaid flowchart, for embedding in Markdown
+    Mermaid,
+    /// GraphML, for yEd and other graph tools
+    Graphml,
+    /// GEXF, for Gephi
+    Gexf,
+}
+
+pub struct ExportOptions {
+    /// Group internal modules into clusters by directory, where the format supports it
+    pub cluster: bool,
+    /// Write JSON on a single line
+    pub compact: bool,
+}
+
+impl OutputFormat {
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "graph.json",
+            OutputFormat::Dot => "graph.dot",
+            OutputFormat::Mermaid => "graph.mmd",
+            OutputFormat::Graphml => "graph.graphml",
+            OutputFormat::Gexf => "graph.gexf",
+        }
+    }
+}
+
+pub fn render_graph(graph: &Graph, format: OutputFormat, options: &ExportOptions) -> String {
+    match format {
+        OutputFormat::Json if options.compact => serde_json::to_string(graph).unwrap(),
+        OutputFormat::Json => serde_json::to_string_pretty(graph).unwrap(),
+        OutputFormat::Dot => dot::render_dot(graph, options.cluster),
+        OutputFormat::Mermaid => mermaid::render_mermaid(graph),
+        OutputFormat::Graphml => graphml::render_graphml(graph),
+        OutputFormat::Gexf => gexf::render_gexf(graph),
+    }
+}
+
+/// Writes the rendered graph to a file, or to stdout when the path is `-`.
+pub fn write_output(path: &str, contents: &[u8], gzip: bool) -> io::Result<()> {
+    let writer: Box<dyn Write> = if path == "-" {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(File::create(path)?)
+    };
+
+    if gzip {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        encoder.write_all(contents)?;
+        encoder.finish()?.flush()
+    } else {
+        let mut writer = writer;
+        writer.write_all(contents)?;
+        writer.flush()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum AttributeType {
+    String,
+    Integer,
+    Double,
+}
+
+/// Node attributes written by the XML formats, in addition to each node's id and label.
+const NODE_ATTRIBUTES: &[(&str, AttributeType)] = &[
+    ("node_type", AttributeType::String),
+    ("path_relative", AttributeType::String),
+    ("path_absolute", AttributeType::String),
+    ("size", AttributeType::Integer),
+    ("afferent_coupling", AttributeType::Integer),
+    ("efferent_coupling", AttributeType::Integer),
+    ("instability", AttributeType::Double),
+    ("transitive_dependencies", AttributeType::Integer),
+    ("depth", AttributeType::Integer),
+];
+
+/// Returns the values of `NODE_ATTRIBUTES` for a node, skipping the size and metrics it doesn't have.
+fn node_attribute_values(node: &Node) -> Vec<(&'static str, String)> {
+    let node_type = serde_json::to_value(&node.node_type).unwrap();
+    let mut values = vec![
+        ("node_type", node_type.as_str().unwrap().to_string()),
+        ("path_relative", node.path_relative.clone()),
+        ("path_absolute", node.path_absolute.clone()),
+    ];
+
+    if let Some(size) = node.size {
+        values.push(("size", size.to_string()));
+    }
+
+    if let Some(metrics) = &node.metrics {
+        values.extend([
+            ("afferent_coupling", metrics.afferent_coupling.to_string()),
+            ("efferent_coupling", metrics.efferent_coupling.to_string()),
+            ("instability", metrics.instability.to_string()),
+            (
+                "transitive_dependencies",
+                metrics.transitive_dependencies.to_string(),
+            ),
+            ("depth", metrics.depth.to_string()),
+        ]);
+    }
+
+    values
+}
+
+/// The name of an edge's kind as written in graph.json, like `DEV`.
+fn edge_kind_name(kind: EdgeKind) -> String {
+    serde_json::to_value(kind)
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,605 @@
+use glob::{Pattern, PatternError};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use crate::config::Config;
+use crate::graph::{generate_graph_from_source, Graph};
+use crate::language::backend_for_config;
+use crate::parse::ParseCache;
+use crate::progress::progress;
+use crate::source::{SourceFilter, SourceTree};
+
+/// The project's files as they were at a git revision.
+///
+/// Files are read straight from the repository's object database, so the
+/// working tree and index are left alone.
+pub struct GitRevision {
+    object_ids_by_path: HashMap<String, String>,
+    sizes_by_path: HashMap<String, u64>,
+    ignore_files: IgnoreFiles,
+    objects: RefCell<ObjectReader>,
+}
+
+impl GitRevision {
+    /// Lists the files under the project directory at `revision`.
+    ///
+    /// The project directory may be anywhere inside the repository; paths are
+    /// relative to it, like they are for the working directory. Files are
+    /// left out by the same ignore files as in the working directory: the
+    /// `.gitignore` and `.ignore` files committed in the project directory and
+    /// its parents, `.git/info/exclude`, and the global excludes file.
+    pub fn new(project_directory: &str, revision: &str) -> Result<Self, Box<dyn Error>> {
+        let commit = run_git(
+            project_directory,
+            &["rev-parse", "--verify", &format!("{}^{{commit}}", revision)],
+        )?;
+        let listing = run_git(
+            project_directory,
+            &["ls-tree", "-r", "-l", "-z", commit.trim()],
+        )?;
+
+        let mut object_ids_by_path = HashMap::new();
+        let mut sizes_by_path = HashMap::new();
+        for entry in listing.split('\0').filter(|entry| !entry.is_empty()) {
+            // Each entry looks like "<mode> <type> <object id> <padded size>\t<path>"
+            let (info, path) = entry
+                .split_once('\t')
+                .ok_or_else(|| format!("Unexpected git ls-tree output: {}", entry))?;
+            let fields: Vec<&str> = info.split_whitespace().collect();
+            if fields.len() != 4 || fields[1] != "blob" {
+                continue;
+            }
+
+            object_ids_by_path.insert(path.to_string(), fields[2].to_string());
+            if let Ok(size) = fields[3].parse() {
+                sizes_by_path.insert(path.to_string(), size);
+            }
+        }
+
+        let mut objects = ObjectReader::new(project_directory)?;
+        let ignore_files = IgnoreFiles::read(
+            project_directory,
+            commit.trim(),
+            &object_ids_by_path,
+            &mut objects,
+        )?;
+
+        Ok(GitRevision {
+            object_ids_by_path,
+            sizes_by_path,
+            ignore_files,
+            objects: RefCell::new(objects),
+        })
+    }
+
+    /// Returns the id of the blob stored at a path, which changes whenever the file does.
+    pub fn object_id(&self, path_relative: &str) -> Option<&str> {
+        self.object_ids_by_path
+            .get(path_relative)
+            .map(|object_id| object_id.as_str())
+    }
+}
+
+impl SourceTree for GitRevision {
+    fn source_paths(&self, filter: &SourceFilter) -> Vec<String> {
+        let mut source_paths: Vec<String> = self
+            .object_ids_by_path
+            .keys()
+            .filter(|path| filter.matches(path) && !self.ignore_files.is_ignored(path))
+            .cloned()
+            .collect();
+        source_paths.sort();
+
+        source_paths
+    }
+
+    fn find_files(&self, pattern: &str) -> Result<Vec<String>, PatternError> {
+        let pattern = Pattern::new(pattern)?;
+        let mut paths: Vec<String> = self
+            .object_ids_by_path
+            .keys()
+            .filter(|path| pattern.matches_path(Path::new(path)))
+            .cloned()
+            .collect();
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    fn read_file(&self, path_relative: &str) -> io::Result<String> {
+        let object_id = self.object_id(path_relative).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in this revision", path_relative),
+            )
+        })?;
+        let contents = self.objects.borrow_mut().read_object(object_id)?;
+
+        String::from_utf8(contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn file_size(&self, path_relative: &str) -> io::Result<u64> {
+        self.sizes_by_path
+            .get(path_relative)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} is not in this revision", path_relative),
+                )
+            })
+    }
+
+    fn content_id(&self, path_relative: &str) -> Option<String> {
+        self.object_id(path_relative)
+            .map(|object_id| object_id.to_string())
+    }
+}
+
+/// Reads objects through a single long-running `git cat-file --batch` process,
+/// rather than starting a new process for every file.
+struct ObjectReader {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl ObjectReader {
+    fn new(repository: &str) -> io::Result<Self> {
+        let mut process = Command::new("git")
+            .arg("-C")
+            .arg(repository)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+
+        Ok(ObjectReader {
+            process,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn read_object(&mut self, object_id: &str) -> io::Result<Vec<u8>> {
+        writeln!(self.stdin, "{}", object_id)?;
+        self.stdin.flush()?;
+
+        // The header looks like "<object id> <type> <size>", or "<object id> missing"
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        let size: usize = header
+            .split_whitespace()
+            .nth(2)
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Failed to read git object {}: {}", object_id, header.trim()),
+                )
+            })?;
+
+        let mut contents = vec![0; size];
+        self.stdout.read_exact(&mut contents)?;
+
+        // Each object is followed by a newline
+        let mut newline = [0; 1];
+        self.stdout.read_exact(&mut newline)?;
+
+        Ok(contents)
+    }
+}
+
+impl Drop for ObjectReader {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Generates the graph for the project as it was at a git revision.
+///
+/// Files whose contents are already in the cache, e.g. from generating the
+/// graph for a nearby revision, aren't parsed again.
+pub fn generate_graph_at_revision(
+    config: &Config,
+    revision: &str,
+    cache: &mut ParseCache,
+) -> Result<Graph, Box<dyn Error>> {
+    progress!("Reading revision {}...", revision);
+    let source = GitRevision::new(&config.path, revision)?;
+    let backend = backend_for_config(config, &source)?;
+    let filter = SourceFilter::from_config(config, backend.as_ref())?;
+
+    Ok(generate_graph_from_source(
+        config,
+        backend.as_ref(),
+        &source,
+        &filter,
+        cache,
+    ))
+}
+
+/// The ignore files that apply to a revision, matching what the working
+/// directory walk respects.
+struct IgnoreFiles {
+    /// Path of the project directory inside the repository, e.g. `app`
+    project_prefix: PathBuf,
+    /// Matchers for each directory with `.gitignore` or `.ignore` files,
+    /// relative to the repository root, deepest first
+    matchers: Vec<(PathBuf, Gitignore)>,
+    /// Matchers for `.git/info/exclude` and the global excludes file, which
+    /// come after the ignore files in the tree
+    excludes: Vec<Gitignore>,
+}
+
+impl IgnoreFiles {
+    fn read(
+        project_directory: &str,
+        commit: &str,
+        object_ids_by_path: &HashMap<String, String>,
+        objects: &mut ObjectReader,
+    ) -> Result<Self, Box<dyn Error>> {
+        let project_prefix =
+            PathBuf::from(run_git(project_directory, &["rev-parse", "--show-prefix"])?.trim());
+
+        // Ignore files inside the project directory, then the ones in the
+        // directories above it, up to the repository root
+        let mut ignore_files: Vec<(PathBuf, String)> = object_ids_by_path
+            .iter()
+            .filter(|(path, _)| is_ignore_file(Path::new(path)))
+            .map(|(path, object_id)| (project_prefix.join(path), object_id.clone()))
+            .collect();
+        let parent_ignore_files: Vec<String> = project_prefix
+            .ancestors()
+            .skip(1)
+            .flat_map(|directory| {
+                [".gitignore", ".ignore"]
+                    .map(|file_name| directory.join(file_name).to_str().unwrap().to_string())
+            })
+            .collect();
+        if !parent_ignore_files.is_empty() {
+            let mut arguments = vec!["ls-tree", "-z", "--full-tree", commit, "--"];
+            arguments.extend(parent_ignore_files.iter().map(String::as_str));
+            let listing = run_git(project_directory, &arguments)?;
+            for entry in listing.split('\0').filter(|entry| !entry.is_empty()) {
+                let Some((info, path)) = entry.split_once('\t') else {
+                    continue;
+                };
+                if let Some(object_id) = info.split_whitespace().nth(2) {
+                    ignore_files.push((PathBuf::from(path), object_id.to_string()));
+                }
+            }
+        }
+        // `.ignore` files take precedence, so their lines are added last
+        ignore_files.sort_by(|(a, _), (b, _)| {
+            (a.ends_with(".ignore"), a).cmp(&(b.ends_with(".ignore"), b))
+        });
+
+        let mut builders: HashMap<PathBuf, GitignoreBuilder> = HashMap::new();
+        for (path, object_id) in ignore_files {
+            let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            let contents = objects.read_object(&object_id)?;
+            let builder = builders
+                .entry(directory.clone())
+                .or_insert_with(|| GitignoreBuilder::new(&directory));
+            for line in String::from_utf8_lossy(&contents).lines() {
+                builder.add_line(Some(path.clone()), line)?;
+            }
+        }
+
+        let mut matchers = builders
+            .into_iter()
+            .map(|(directory, builder)| Ok((directory, builder.build()?)))
+            .collect::<Result<Vec<_>, ignore::Error>>()?;
+        matchers.sort_by_key(|(directory, _)| std::cmp::Reverse(directory.components().count()));
+
+        // These aren't part of the revision, so they're read from disk, like
+        // the working directory walk does
+        let mut excludes = vec![];
+        let exclude_path = run_git(
+            project_directory,
+            &["rev-parse", "--git-path", "info/exclude"],
+        )?;
+        let exclude_path = Path::new(project_directory).join(exclude_path.trim());
+        if exclude_path.is_file() {
+            let mut builder = GitignoreBuilder::new("");
+            if let Some(error) = builder.add(&exclude_path) {
+                return Err(error.into());
+            }
+            excludes.push(builder.build()?);
+        }
+        let (global_excludes, _) = GitignoreBuilder::new("").build_global();
+        excludes.push(global_excludes);
+
+        Ok(IgnoreFiles {
+            project_prefix,
+            matchers,
+            excludes,
+        })
+    }
+
+    /// Whether the file is ignored, going by the ignore file closest to it that
+    /// mentions it or one of its parent directories, then by the excludes.
+    fn is_ignored(&self, path_relative: &str) -> bool {
+        let path = self.project_prefix.join(path_relative);
+        let matchers = self
+            .matchers
+            .iter()
+            .filter(|(directory, _)| path.starts_with(directory))
+            .map(|(_, matcher)| matcher)
+            .chain(self.excludes.iter());
+        for matcher in matchers {
+            let matched = matcher.matched_path_or_any_parents(&path, false);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+
+        false
+    }
+}
+
+fn is_ignore_file(path: &Path) -> bool {
+    path.file_name() == Some(".gitignore".as_ref()) || path.file_name() == Some(".ignore".as_ref())
+}
+
+/// A commit on the first-parent history of a branch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub id: String,
+    /// Committer time, in seconds since the Unix epoch
+    pub timestamp: i64,
+    /// Committer time, in ISO 8601 format
+    pub date: String,
+}
+
+/// Lists the commits that touched the project directory, oldest first.
+///
+/// Only the first-parent history is followed, so commits merged in from
+/// other branches show up once, as their merge commit.
+pub fn list_commits(
+    project_directory: &str,
+    from: Option<&str>,
+    to: &str,
+) -> Result<Vec<Commit>, Box<dyn Error>> {
+    let range = match from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    };
+    let output = run_git(
+        project_directory,
+        &[
+            "log",
+            "--first-parent",
+            "--reverse",
+            "--format=%H %ct %cI",
+            &range,
+            "--",
+            ".",
+        ],
+    )?;
+
+    let mut commits = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.split(' ');
+        let (Some(id), Some(timestamp), Some(date)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("unexpected git log output: {:?}", line).into());
+        };
+        commits.push(Commit {
+            id: id.to_string(),
+            timestamp: timestamp.parse()?,
+            date: date.to_string(),
+        });
+    }
+
+    Ok(commits)
+}
+
+fn run_git(repository: &str, args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs;
+    use uuid::Uuid;
+
+    use crate::source::WorkingDirectory;
+
+    fn git(repository: &std::path::Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(repository)
+            .args([
+                "-c",
+                "user.name=depdoc",
+                "-c",
+                "user.email=depdoc@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_git_revision_reads_committed_files() {
+        let repository = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        let project = repository.join("app");
+        fs::create_dir_all(project.join("src/node_modules/lib")).unwrap();
+        fs::write(project.join("src/index.ts"), "import \"./a\";\n").unwrap();
+        fs::write(project.join("src/README.md"), "# App\n").unwrap();
+        fs::write(project.join("src/node_modules/lib/index.js"), "").unwrap();
+        fs::create_dir_all(project.join("src/gen")).unwrap();
+        fs::write(project.join("src/gen/api.ts"), "").unwrap();
+        fs::write(project.join(".ignore"), "src/gen/\n").unwrap();
+        fs::write(repository.join("root.ts"), "").unwrap();
+
+        git(&repository, &["init", "-q"]);
+        git(&repository, &["add", "-A"]);
+        git(&repository, &["commit", "-q", "-m", "Initial commit"]);
+        fs::write(project.join("src/index.ts"), "import \"./b\";\n").unwrap();
+
+        let extensions = vec!["ts".to_string()];
+        let filter = SourceFilter::new(extensions, &[], &[]).unwrap();
+        let revision = GitRevision::new(project.to_str().unwrap(), "HEAD").unwrap();
+
+        assert_eq!(revision.source_paths(&filter), vec!["src/index.ts"]);
+        assert_eq!(
+            revision.find_files("src/*.md").unwrap(),
+            vec!["src/README.md"]
+        );
+        assert_eq!(
+            revision.read_file("src/index.ts").unwrap(),
+            "import \"./a\";\n"
+        );
+        assert_eq!(revision.read_file("src/README.md").unwrap(), "# App\n");
+        assert_eq!(revision.file_size("src/README.md").unwrap(), 6);
+        assert!(revision.read_file("src/missing.ts").is_err());
+        assert!(GitRevision::new(project.to_str().unwrap(), "missing-branch").is_err());
+
+        fs::remove_dir_all(&repository).unwrap();
+    }
+
+    #[test]
+    fn test_git_revision_ignores_like_working_directory() {
+        let repository = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        let project = repository.join("packages/app");
+        for path in [
+            "src/index.ts",
+            "src/api.gen.ts",
+            "src/scratch.ts",
+            "src/vendor/lib.ts",
+        ] {
+            let path = project.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::write(repository.join(".gitignore"), "*.gen.ts\n").unwrap();
+        fs::write(repository.join("packages/.ignore"), "vendor/\n").unwrap();
+
+        git(&repository, &["init", "-q"]);
+        // Committed anyway, as ignore files only keep untracked files out
+        git(&repository, &["add", "-A", "--force"]);
+        git(&repository, &["commit", "-q", "-m", "Initial commit"]);
+        fs::write(repository.join(".git/info/exclude"), "scratch.ts\n").unwrap();
+
+        let filter = SourceFilter::new(vec!["ts".to_string()], &[], &[]).unwrap();
+        let revision = GitRevision::new(project.to_str().unwrap(), "HEAD").unwrap();
+        let working_directory = WorkingDirectory::new(project.to_str().unwrap());
+
+        assert_eq!(revision.source_paths(&filter), vec!["src/index.ts"]);
+        assert_eq!(
+            revision.source_paths(&filter),
+            working_directory.source_paths(&filter)
+        );
+
+        fs::remove_dir_all(&repository).unwrap();
+    }
+
+    #[test]
+    fn test_generate_graph_at_revision_reads_manifests_from_revision() {
+        let repository = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        for (path, contents) in [
+            ("go.mod", "module example.com/app\n"),
+            (
+                "main.go",
+                "package main\n\nimport \"example.com/app/util\"\n",
+            ),
+            ("util/util.go", "package util\n"),
+            ("vendor/example.org/lib/lib.go", "package lib\n"),
+            ("util/testdata/fixture.go", "package fixture\n"),
+        ] {
+            let path = repository.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        git(&repository, &["init", "-q"]);
+        git(&repository, &["add", "-A"]);
+        git(&repository, &["commit", "-q", "-m", "Initial commit"]);
+        fs::write(repository.join("go.mod"), "module example.com/renamed\n").unwrap();
+
+        let config: Config = serde_yaml::from_str(&format!(
+            "title: test\nlanguage: go\npath: {}\n",
+            repository.to_str().unwrap()
+        ))
+        .unwrap();
+        let graph =
+            generate_graph_at_revision(&config, "HEAD", &mut ParseCache::default()).unwrap();
+
+        let mut paths: Vec<&str> = graph
+            .nodes
+            .iter()
+            .map(|node| node.path_relative.as_str())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec![".", "util"]);
+        assert_eq!(graph.edges.len(), 1);
+
+        fs::remove_dir_all(&repository).unwrap();
+    }
+
+    #[test]
+    fn test_list_commits() {
+        let repository = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        let project = repository.join("app");
+        fs::create_dir_all(&project).unwrap();
+
+        git(&repository, &["init", "-q"]);
+        fs::write(project.join("a.ts"), "").unwrap();
+        git(&repository, &["add", "-A"]);
+        git(&repository, &["commit", "-q", "-m", "Add a"]);
+        fs::write(repository.join("other.txt"), "").unwrap();
+        git(&repository, &["add", "-A"]);
+        git(&repository, &["commit", "-q", "-m", "Outside the project"]);
+        fs::write(project.join("b.ts"), "").unwrap();
+        git(&repository, &["add", "-A"]);
+        git(&repository, &["commit", "-q", "-m", "Add b"]);
+
+        let project = project.to_str().unwrap();
+        let commits = list_commits(project, None, "HEAD").unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(
+            commits[1].id,
+            run_git(project, &["rev-parse", "HEAD"]).unwrap().trim()
+        );
+        assert!(commits[0].timestamp <= commits[1].timestamp);
+
+        let commits = list_commits(project, Some(&commits[0].id), "HEAD").unwrap();
+        assert_eq!(commits.len(), 1);
+
+        fs::remove_dir_all(&repository).unwrap();
+    }
+}
@@ -1,66 +1,230 @@
-use glob::glob;
-use serde::Serialize;
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use flate2::read::GzDecoder;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
-use crate::config::read_config_file;
-use crate::imports::{resolve_import, ResolvedImport};
-use crate::parse::extract_imports;
-
-#[derive(Serialize)]
+use crate::config::Config;
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::language::{backend_for_config, LanguageBackend};
+use crate::metrics::{attach_metrics, NodeMetrics};
+use crate::parse::{ParseCache, ParsedFile};
+use crate::progress::progress;
+use crate::source::{SourceFilter, SourceTree, WorkingDirectory};
+
+/// Version of the graph.json format.
+///
+/// Changes to the format are additive: new fields are optional, and existing
+/// fields keep their names and meaning. This only increases when a change
+/// can't be made that way, so consumers should check it before reading a graph.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Graph {
+    /// Version of the graph.json format. Graphs written before the format was
+    /// versioned don't have this field, and read as version 0.
+    #[serde(default)]
+    pub schema_version: u32,
     pub title: String,
+    /// The `language` from the config, which tells how the labels of external
+    /// nodes map to packages. Graphs from older versions don't have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Node {
     pub id: String,
     pub node_type: NodeType,
     pub label: String,
     pub path_absolute: String,
     pub path_relative: String,
+
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<NodeMetrics>,
+
+    /// Size of the file in bytes, for assets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub enum NodeType {
     #[serde(rename = "INTERNAL")]
     Internal,
 
     #[serde(rename = "EXTERNAL")]
     External,
+
+    /// A module of the language's standard library
+    #[serde(rename = "STDLIB")]
+    Stdlib,
+
+    #[serde(rename = "GROUP")]
+    Group,
+
+    /// A CSS, SCSS, Sass or LESS file of the project
+    #[serde(rename = "STYLESHEET")]
+    Stylesheet,
+
+    /// A file imported by code that isn't code itself, like an image or JSON
+    #[serde(rename = "ASSET")]
+    Asset,
 }
 
-#[derive(Serialize, Debug)]
+impl NodeType {
+    /// Whether the node is one of the project's own files or packages.
+    pub fn is_internal(&self) -> bool {
+        matches!(
+            self,
+            NodeType::Internal | NodeType::Stylesheet | NodeType::Asset
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Edge {
     pub id: String,
     pub source_id: String,
     pub target_id: String,
+
+    /// Number of imports this edge stands for, set when edges are aggregated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<usize>,
+
+    /// What kind of dependency this edge is, for graphs of packages rather than modules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<EdgeKind>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// A regular dependency
+    #[serde(rename = "NORMAL")]
+    Normal,
+    /// A regular dependency that's only used when a feature enables it
+    #[serde(rename = "OPTIONAL")]
+    Optional,
+    /// A dependency of the tests, examples and benchmarks only
+    #[serde(rename = "DEV")]
+    Dev,
+    /// A dependency of the build script
+    #[serde(rename = "BUILD")]
+    Build,
 }
 
-pub fn generate_graph(config_location: &str) -> Graph {
-    println!("Config location: {}", config_location);
+/// Returns the JSON Schema describing graph.json, as published in `schema/graph.schema.json`.
+pub fn graph_json_schema() -> String {
+    let schema = schemars::schema_for!(Graph);
+    let mut schema_json = serde_json::to_string_pretty(&schema).unwrap();
+    schema_json.push('\n');
 
-    let config = read_config_file(config_location).unwrap();
+    schema_json
+}
+
+/// Reads a graph written by `generate`, decompressing it if the file name ends in `.gz`.
+pub fn read_graph_file(file_path: &str) -> Result<Graph, Box<dyn std::error::Error>> {
+    let file = File::open(file_path)?;
+    let mut reader: Box<dyn Read> = if file_path.ends_with(".gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+
+    let graph: Graph = serde_json::from_str(&content)?;
+
+    Ok(graph)
+}
+
+/// Generates the graph for the files currently in the project directory.
+pub fn generate_graph(config: &Config) -> Result<Graph, Box<dyn Error>> {
+    let source = WorkingDirectory::new(&config.path);
+    let backend = backend_for_config(config, &source)?;
+    let filter = SourceFilter::from_config(config, backend.as_ref())?;
+
+    let mut cache = ParseCache::default();
+    Ok(generate_graph_from_source(
+        config,
+        backend.as_ref(),
+        &source,
+        &filter,
+        &mut cache,
+    ))
+}
 
-    println!("Finding source paths...");
-    let paths = get_source_paths(&config.path);
-    println!("Found {} source files.", paths.len());
+/// Builds the graph from the files in `source` that `filter` lets through,
+/// reading them the way the language backend says to.
+pub fn generate_graph_from_source(
+    config: &Config,
+    backend: &dyn LanguageBackend,
+    source: &dyn SourceTree,
+    filter: &SourceFilter,
+    cache: &mut ParseCache,
+) -> Graph {
+    progress!("Finding source paths...");
+    let paths = source.source_paths(filter);
+    progress!("Found {} source files.", paths.len());
+
+    progress!("Extracting imports from files...");
+    let mut imports_by_path: HashMap<&str, Vec<UnresolvedImport>> = HashMap::new();
+    let mut node_paths_by_path: HashMap<&str, String> = HashMap::new();
+    for path_relative in paths.iter() {
+        // Only backends that give each file its own node read assets, so the
+        // file's path is the node's path here
+        let is_asset = matches!(backend.node_type(path_relative), NodeType::Asset);
+        let parsed_file = if is_asset {
+            // Assets don't import anything, and may not even be text
+            None
+        } else {
+            read_parsed_file(backend, source, path_relative, cache)
+        };
+        let (imports, package) = match parsed_file {
+            Some(parsed_file) => (parsed_file.imports, parsed_file.package),
+            None => (vec![], None),
+        };
+        imports_by_path.insert(path_relative, imports);
+        node_paths_by_path.insert(
+            path_relative,
+            backend.node_path(path_relative, package.as_deref()),
+        );
+    }
+
+    // A node that groups files, like a package, points at the directory of
+    // its first file, as its own path needn't exist
+    let mut node_paths: Vec<(String, PathBuf)> = vec![];
+    let mut seen_node_paths: HashSet<String> = HashSet::new();
+    for path_relative in paths.iter() {
+        let node_path = &node_paths_by_path[path_relative.as_str()];
+        if seen_node_paths.insert(node_path.clone()) {
+            let path = Path::new(path_relative);
+            let path = if node_path == path_relative {
+                path
+            } else {
+                path.parent().unwrap()
+            };
+            node_paths.push((node_path.clone(), Path::new(&config.path).join(path)));
+        }
+    }
 
-    let internal_nodes: Vec<Node> = paths
+    let internal_nodes: Vec<Node> = node_paths
         .iter()
-        .map(|path| {
-            let path_relative = path.strip_prefix(&config.path).unwrap().to_str().unwrap();
-            Node {
-                id: Uuid::new_v4().to_string(),
-                node_type: NodeType::Internal,
-                label: get_node_label(path_relative),
-                path_absolute: path.to_str().unwrap().to_string(),
-                path_relative: path_relative.to_string(),
-            }
+        .map(|(path_relative, path_absolute)| Node {
+            id: Uuid::new_v4().to_string(),
+            node_type: backend.node_type(path_relative),
+            label: get_node_label(path_relative),
+            path_absolute: path_absolute.to_str().unwrap().to_string(),
+            path_relative: path_relative.to_string(),
+            metrics: None,
+            size: None,
         })
         .collect();
 
@@ -71,17 +235,32 @@ pub fn generate_graph(config_location: &str) -> Graph {
 
     let mut external_nodes_by_name: HashMap<String, Node> = HashMap::new();
 
-    println!("Extracting imports from files...");
+    let mut resolved_imports_by_node_path: HashMap<String, Vec<ResolvedImport>> = HashMap::new();
+    for path_relative in paths.iter() {
+        let node = nodes_by_path[&node_paths_by_path[path_relative.as_str()]];
+        let unresolved_imports = imports_by_path
+            .remove(path_relative.as_str())
+            .unwrap_or_default();
+
+        let resolved_imports = resolved_imports_by_node_path
+            .entry(node.path_relative.clone())
+            .or_default();
+        for import in unresolved_imports {
+            match backend.resolve_import(import, node, &nodes_by_path) {
+                // Several imports of the same module make one edge
+                Some(resolved_import) if !resolved_imports.contains(&resolved_import) => {
+                    resolved_imports.push(resolved_import)
+                }
+                _ => {}
+            }
+        }
+    }
+
     let mut edges: Vec<Edge> = vec![];
     for node in internal_nodes.iter() {
-        println!("  Parsing file: {:?}", node.path_relative);
-        let contents = fs::read_to_string(&node.path_absolute).unwrap();
-        let unresolved_imports = extract_imports(&contents);
-
-        let resolved_imports: Vec<ResolvedImport> = unresolved_imports
-            .into_iter()
-            .map(|import| resolve_import(import, &node, &nodes_by_path, &config.module_resolution))
-            .collect();
+        let resolved_imports = resolved_imports_by_node_path
+            .remove(&node.path_relative)
+            .unwrap_or_default();
 
         for resolved_import in &resolved_imports {
             match resolved_import {
@@ -93,10 +272,12 @@ pub fn generate_graph(config_location: &str) -> Graph {
                     if !external_nodes_by_name.contains_key(target_module_name) {
                         let external_node = Node {
                             id: Uuid::new_v4().to_string(),
-                            node_type: NodeType::External,
+                            node_type: backend.external_node_type(target_module_name),
                             label: target_module_name.clone(),
                             path_absolute: "".to_string(), // TODO: yucky type hack
                             path_relative: "".to_string(),
+                            metrics: None,
+                            size: None,
                         };
                         external_nodes_by_name.insert(target_module_name.clone(), external_node);
                     }
@@ -114,6 +295,8 @@ pub fn generate_graph(config_location: &str) -> Graph {
                     id: Uuid::new_v4().to_string(),
                     source_id: node.id.clone(),
                     target_id: target_node_id.clone(),
+                    weight: None,
+                    kind: None,
                 },
                 ResolvedImport::ExternalImport { target_module_name } => {
                     let related_external_node =
@@ -122,70 +305,160 @@ pub fn generate_graph(config_location: &str) -> Graph {
                         id: Uuid::new_v4().to_string(),
                         source_id: node.id.clone(),
                         target_id: related_external_node.id.clone(),
+                        weight: None,
+                        kind: None,
                     }
                 }
             })
             .collect();
         edges.extend(import_edges);
     }
-    println!("Found {} edges between modules.", edges.len());
+    progress!("Found {} edges between modules.", edges.len());
 
-    println!(
+    progress!(
         "Added {} extra nodes for external modules.",
         external_nodes_by_name.len()
     );
 
+    // Assets only belong in the graph when a module imports them
+    let imported_node_ids: HashSet<&str> =
+        edges.iter().map(|edge| edge.target_id.as_str()).collect();
     let mut combined_nodes: Vec<Node> = vec![];
-    combined_nodes.extend(internal_nodes.clone());
+    let mut asset_count = 0;
+    for node in internal_nodes.iter() {
+        if !matches!(node.node_type, NodeType::Asset) {
+            combined_nodes.push(node.clone());
+        } else if imported_node_ids.contains(node.id.as_str()) {
+            combined_nodes.push(Node {
+                size: source.file_size(&node.path_relative).ok(),
+                ..node.clone()
+            });
+            asset_count += 1;
+        }
+    }
+    if asset_count > 0 {
+        progress!("Added {} imported assets.", asset_count);
+    }
     combined_nodes.extend(external_nodes_by_name.values().cloned());
 
-    let graph = Graph {
-        title: config.title,
+    let mut graph = Graph {
+        schema_version: SCHEMA_VERSION,
+        title: config.title.clone(),
+        language: Some(config.language.clone()),
         nodes: combined_nodes,
         edges,
     };
 
+    progress!("Computing module metrics...");
+    attach_metrics(&mut graph);
+
     graph
 }
 
-fn get_source_paths(root_directory: &str) -> Vec<PathBuf> {
-    let mut combined_results = Vec::new();
+/// Reads and parses a file, or takes what was parsed from the same contents
+/// before from `cache`. Files that can't be read are skipped with a warning.
+fn read_parsed_file(
+    backend: &dyn LanguageBackend,
+    source: &dyn SourceTree,
+    path_relative: &str,
+    cache: &mut ParseCache,
+) -> Option<ParsedFile> {
+    let content_id = source.content_id(path_relative);
+    if let Some(cached_file) = content_id
+        .as_ref()
+        .and_then(|content_id| cache.get(content_id))
+    {
+        return Some(cached_file.clone());
+    }
 
-    combined_results.extend(get_paths(root_directory, "js"));
-    combined_results.extend(get_paths(root_directory, "jsx"));
-    combined_results.extend(get_paths(root_directory, "ts"));
-    combined_results.extend(get_paths(root_directory, "tsx"));
+    progress!("  Parsing file: {:?}", path_relative);
+    let contents = match source.read_file(path_relative) {
+        Ok(contents) => contents,
+        Err(error) => {
+            // E.g. a file that isn't UTF-8, which keeps its node but no imports
+            eprintln!("Warning: {}: couldn't read file, {}", path_relative, error);
+            return None;
+        }
+    };
+    let parsed_file = backend.parse_file(path_relative, &contents);
+    for warning in parsed_file.warnings.iter() {
+        eprintln!(
+            "Warning: {}:{}:{}: syntax error, {}",
+            path_relative, warning.line, warning.column, warning.message
+        );
+    }
+    if let Some(content_id) = content_id {
+        cache.insert(content_id, parsed_file.clone());
+    }
 
-    combined_results
+    Some(parsed_file)
 }
 
-fn get_paths(root_directory: &str, file_extension: &str) -> Vec<PathBuf> {
-    let glob_pattern = format!("{}/**/*.{}", root_directory, file_extension);
-
-    let paths: Vec<PathBuf> = glob(&glob_pattern)
-        .expect("Failed to read glob pattern")
-        .filter_map(|entry| match entry {
-            Ok(path) => Some(path),
-            Err(_) => None,
-        })
-        .filter(|path| !contains_node_modules(&path))
-        .collect();
+/// Keeps only the modules reachable from `root`, following at most `depth` imports.
+///
+/// The root is matched against internal modules' relative paths and external
+/// modules' names.
+pub fn scope_graph(graph: &Graph, root: &str, depth: Option<usize>) -> Option<Graph> {
+    let root_node = graph.nodes.iter().find(|node| match node.node_type {
+        NodeType::External | NodeType::Stdlib => node.label == root,
+        _ => node.path_relative == root,
+    })?;
+
+    let mut edges_by_source: HashMap<&str, Vec<&Edge>> = HashMap::new();
+    for edge in graph.edges.iter() {
+        edges_by_source
+            .entry(edge.source_id.as_str())
+            .or_default()
+            .push(edge);
+    }
 
-    paths
-}
+    let mut kept_ids: HashSet<&str> = HashSet::new();
+    kept_ids.insert(&root_node.id);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::new();
+    queue.push_back((&root_node.id, 0));
+    while let Some((node_id, distance)) = queue.pop_front() {
+        if depth.is_some_and(|depth| distance >= depth) {
+            continue;
+        }
+        for edge in edges_by_source.get(node_id).into_iter().flatten() {
+            if kept_ids.insert(&edge.target_id) {
+                queue.push_back((&edge.target_id, distance + 1));
+            }
+        }
+    }
 
-fn contains_node_modules(path: &PathBuf) -> bool {
-    path.iter()
-        .any(|component| component == "node_modules" || component == "build" || component == "dist")
+    Some(Graph {
+        schema_version: graph.schema_version,
+        title: graph.title.clone(),
+        language: graph.language.clone(),
+        nodes: graph
+            .nodes
+            .iter()
+            .filter(|node| kept_ids.contains(node.id.as_str()))
+            .cloned()
+            .collect(),
+        edges: graph
+            .edges
+            .iter()
+            .filter(|edge| {
+                kept_ids.contains(edge.source_id.as_str())
+                    && kept_ids.contains(edge.target_id.as_str())
+            })
+            .cloned()
+            .collect(),
+    })
 }
 
 pub fn get_node_label(path_relative: &str) -> String {
     let path = PathBuf::from(path_relative);
 
-    let file_stem = path.file_stem().unwrap().to_str().unwrap();
+    // The project root, as a package directory
+    let Some(file_stem) = path.file_stem().and_then(|file_stem| file_stem.to_str()) else {
+        return path_relative.to_string();
+    };
     let file_name = path.file_name().unwrap().to_str().unwrap();
 
-    if file_stem == "index"
+    if (file_stem == "index" || file_name == "mod.rs" || file_name == "__init__.py")
         && path.parent().is_some()
         && path.parent().unwrap().file_name().is_some()
     {
@@ -206,6 +479,9 @@ pub fn get_node_label(path_relative: &str) -> String {
 mod test {
     use super::*;
 
+    use crate::source::MemorySourceTree;
+    use std::fs;
+
     #[test]
     fn test_get_node_label_root_index() {
         let path = "index.ts";
@@ -233,4 +509,245 @@ mod test {
         let label = get_node_label(path);
         assert_eq!(label, "SomeContext/index.ts");
     }
+
+    #[test]
+    fn test_get_node_label_rust_mod() {
+        let path = "src/export/mod.rs";
+        let label = get_node_label(path);
+        assert_eq!(label, "export/mod.rs");
+    }
+
+    #[test]
+    fn test_get_node_label_python_package() {
+        let path = "src/app/__init__.py";
+        let label = get_node_label(path);
+        assert_eq!(label, "app/__init__.py");
+    }
+
+    fn test_scope_graph_fixture() -> Graph {
+        let node = |id: &str, node_type: NodeType, path_relative: &str| Node {
+            id: id.to_string(),
+            node_type,
+            label: id.to_string(),
+            path_absolute: "".to_string(),
+            path_relative: path_relative.to_string(),
+            metrics: None,
+            size: None,
+        };
+        let edge = |source_id: &str, target_id: &str| Edge {
+            id: format!("{}->{}", source_id, target_id),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            weight: None,
+            kind: None,
+        };
+
+        Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: vec![
+                node("a", NodeType::Internal, "a.ts"),
+                node("b", NodeType::Internal, "b.ts"),
+                node("c", NodeType::Internal, "c.ts"),
+                node("d", NodeType::Internal, "d.ts"),
+                node("react", NodeType::External, ""),
+            ],
+            edges: vec![
+                edge("a", "b"),
+                edge("b", "c"),
+                edge("b", "react"),
+                edge("d", "a"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_scope_graph_with_depth() {
+        let graph = scope_graph(&test_scope_graph_fixture(), "a.ts", Some(1)).unwrap();
+
+        let node_ids: Vec<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(node_ids, vec!["a", "b"]);
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_scope_graph_without_depth() {
+        let graph = scope_graph(&test_scope_graph_fixture(), "a.ts", None).unwrap();
+
+        let node_ids: Vec<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(node_ids, vec!["a", "b", "c", "react"]);
+        assert_eq!(graph.edges.len(), 3);
+    }
+
+    #[test]
+    fn test_scope_graph_unknown_root() {
+        assert!(scope_graph(&test_scope_graph_fixture(), "missing.ts", None).is_none());
+    }
+
+    #[test]
+    fn test_graph_json_schema_is_published() {
+        let published = include_str!("../schema/graph.schema.json");
+        assert_eq!(
+            graph_json_schema(),
+            published,
+            "graph.json schema changed, run `cargo run -- schema --output schema/graph.schema.json`"
+        );
+    }
+
+    #[test]
+    fn test_read_unversioned_graph() {
+        let graph_json = r#"{
+            "title": "Old graph",
+            "nodes": [
+                {
+                    "id": "a",
+                    "node_type": "INTERNAL",
+                    "label": "a.ts",
+                    "path_absolute": "/repo/a.ts",
+                    "path_relative": "a.ts"
+                }
+            ],
+            "edges": [
+                { "id": "e", "source_id": "a", "target_id": "a" }
+            ]
+        }"#;
+
+        let graph: Graph = serde_json::from_str(graph_json).unwrap();
+
+        assert_eq!(graph.schema_version, 0);
+        assert!(graph.nodes[0].metrics.is_none());
+        assert_eq!(graph.edges[0].weight, None);
+    }
+
+    #[test]
+    fn test_read_graph_with_unknown_fields() {
+        let graph_json = r#"{
+            "schema_version": 1,
+            "title": "Newer graph",
+            "added_later": true,
+            "nodes": [
+                {
+                    "id": "a",
+                    "node_type": "INTERNAL",
+                    "label": "a.ts",
+                    "path_absolute": "/repo/a.ts",
+                    "path_relative": "a.ts",
+                    "afferent_coupling": 0,
+                    "efferent_coupling": 0,
+                    "instability": 0.0,
+                    "transitive_dependencies": 0,
+                    "depth": 0,
+                    "added_later": 1
+                }
+            ],
+            "edges": []
+        }"#;
+
+        let graph: Graph = serde_json::from_str(graph_json).unwrap();
+
+        assert_eq!(graph.schema_version, 1);
+        assert_eq!(graph.nodes[0].metrics.as_ref().unwrap().depth, 0);
+    }
+
+    #[test]
+    fn test_generate_graph_with_assets() {
+        let config: Config =
+            serde_yaml::from_str("title: test\nlanguage: typescript\npath: /repo\n").unwrap();
+        let source = MemorySourceTree {
+            files: vec![
+                ("src/App.tsx", "import logo from \"./logo.svg\";\n"),
+                ("src/logo.svg", "<svg></svg>"),
+                ("src/unused.png", "not imported"),
+            ],
+        };
+        let backend = backend_for_config(&config, &source).unwrap();
+        let filter = SourceFilter::from_config(&config, backend.as_ref()).unwrap();
+
+        let graph = generate_graph_from_source(
+            &config,
+            backend.as_ref(),
+            &source,
+            &filter,
+            &mut ParseCache::default(),
+        );
+
+        let paths: Vec<&str> = graph
+            .nodes
+            .iter()
+            .map(|node| node.path_relative.as_str())
+            .collect();
+        assert_eq!(paths, vec!["src/App.tsx", "src/logo.svg"]);
+        assert!(matches!(graph.nodes[1].node_type, NodeType::Asset));
+        assert_eq!(graph.nodes[1].size, Some(11));
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_generate_graph_with_packages() {
+        let config: Config =
+            serde_yaml::from_str("title: test\nlanguage: java\npath: /repo\npackages: true\n")
+                .unwrap();
+        let source = MemorySourceTree {
+            files: vec![
+                (
+                    "src/main/java/com/example/web/UserController.java",
+                    "package com.example.web;\n\nimport com.example.model.User;\n",
+                ),
+                (
+                    "src/main/java/com/example/model/User.java",
+                    "package com.example.model;\n",
+                ),
+                (
+                    "src/main/kotlin/com/example/model/Group.kt",
+                    "package com.example.model\n",
+                ),
+            ],
+        };
+        let backend = backend_for_config(&config, &source).unwrap();
+        let filter = SourceFilter::from_config(&config, backend.as_ref()).unwrap();
+
+        let graph = generate_graph_from_source(
+            &config,
+            backend.as_ref(),
+            &source,
+            &filter,
+            &mut ParseCache::default(),
+        );
+
+        let paths: Vec<(&str, &str)> = graph
+            .nodes
+            .iter()
+            .map(|node| (node.path_relative.as_str(), node.path_absolute.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("com/example/web", "/repo/src/main/java/com/example/web"),
+                ("com/example/model", "/repo/src/main/java/com/example/model"),
+            ]
+        );
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_generate_graph_skips_unreadable_files() {
+        let root = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/index.ts"), "import \"./legacy\";\n").unwrap();
+        // "café" in Latin-1, which isn't valid UTF-8
+        fs::write(root.join("src/legacy.ts"), b"// caf\xe9\n").unwrap();
+        let config: Config = serde_yaml::from_str(&format!(
+            "title: test\nlanguage: typescript\npath: {}\n",
+            root.display()
+        ))
+        .unwrap();
+
+        let graph = generate_graph(&config).unwrap();
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::ModuleResolutionItem;
+use crate::graph::Node;
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::file_extension;
+use crate::source::normalize_path;
+
+/// Extensions of the GraphQL documents read along with the code.
+pub const GRAPHQL_EXTENSIONS: [&str; 2] = ["graphql", "gql"];
+
+pub fn is_graphql_document(path_relative: &str) -> bool {
+    file_extension(path_relative)
+        .is_some_and(|extension| GRAPHQL_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Finds the documents pulled in by `#import "./Fragment.graphql"` lines, as
+/// understood by `graphql-tag/loader` and `graphql-import`.
+pub fn parse_graphql_imports(file_contents: &str) -> Vec<UnresolvedImport> {
+    let mut imports: Vec<UnresolvedImport> = vec![];
+
+    for line in file_contents.lines() {
+        let Some(rest) = line.trim_start().strip_prefix("#import") else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        let Some(string_end) = rest[1..].find(quote) else {
+            continue;
+        };
+
+        let module_name = rest[1..string_end + 1].to_string();
+        if !imports
+            .iter()
+            .any(|import| import.module_name == module_name)
+        {
+            imports.push(UnresolvedImport { module_name });
+        }
+    }
+
+    imports
+}
+
+/// Works out which document an `#import` in the GraphQL document `node` loads.
+///
+/// Relative paths are looked up next to the importing file, other paths go
+/// through the config's module resolution, and whatever isn't found comes
+/// from a package and is external.
+pub fn resolve_graphql_import(
+    import: UnresolvedImport,
+    node: &Node,
+    nodes_by_path: &HashMap<String, &Node>,
+    module_resolution: &Option<Vec<ModuleResolutionItem>>,
+) -> Option<ResolvedImport> {
+    let module_name = import.module_name;
+
+    let mut module_paths = vec![];
+    if module_name.starts_with('.') {
+        let directory = Path::new(&node.path_relative).parent().unwrap();
+        module_paths.push(directory.join(&module_name));
+    }
+    for resolution_item in module_resolution.iter().flatten() {
+        if module_name.starts_with(&resolution_item.pattern) {
+            module_paths.push(PathBuf::from(
+                module_name.replace(&resolution_item.pattern, &resolution_item.replacement),
+            ));
+        }
+    }
+
+    for module_path in module_paths {
+        let candidate = normalize_path(&module_path);
+        let candidate = candidate.to_str().unwrap();
+        if let Some(target_node) = nodes_by_path.get(candidate) {
+            if target_node.id == node.id {
+                return None;
+            }
+            return Some(ResolvedImport::InternalImport {
+                target_path: candidate.to_string(),
+                target_node_id: target_node.id.clone(),
+            });
+        }
+    }
+
+    Some(ResolvedImport::ExternalImport {
+        target_module_name: module_name,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::NodeType;
+    use crate::language::{resolve_test_import, test_nodes};
+
+    #[test]
+    fn test_parse_graphql_imports() {
+        let file_contents = r#"#import "./UserFields.graphql"
+  #import '../shared/PageInfo.gql'
+# import "not-an-import.graphql"
+#import "./UserFields.graphql"
+
+query Users {
+  users { ...UserFields }
+}
+"#;
+
+        let module_names: Vec<String> = parse_graphql_imports(file_contents)
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect();
+
+        assert_eq!(
+            module_names,
+            vec!["./UserFields.graphql", "../shared/PageInfo.gql"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_graphql_import() {
+        let paths = [
+            "src/users/Users.graphql",
+            "src/users/UserFields.graphql",
+            "src/shared/PageInfo.gql",
+        ];
+        let nodes = test_nodes(&paths, |_| NodeType::Internal);
+        let module_resolution = Some(vec![ModuleResolutionItem {
+            pattern: "@shared/".to_string(),
+            replacement: "src/shared/".to_string(),
+        }]);
+
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                resolve_graphql_import(import, node, nodes_by_path, &module_resolution)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(
+            resolve(0, "./UserFields.graphql"),
+            some("src/users/UserFields.graphql")
+        );
+        assert_eq!(
+            resolve(0, "../shared/PageInfo.gql"),
+            some("src/shared/PageInfo.gql")
+        );
+        assert_eq!(
+            resolve(1, "@shared/PageInfo.gql"),
+            some("src/shared/PageInfo.gql")
+        );
+        assert_eq!(resolve(1, "./UserFields.graphql"), None);
+        assert_eq!(
+            resolve(0, "relay-fragments/Node.graphql"),
+            some("external relay-fragments/Node.graphql")
+        );
+    }
+}
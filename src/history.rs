@@ -0,0 +1,232 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::config::Config;
+use crate::diff::external_packages;
+use crate::git::{generate_graph_at_revision, list_commits, Commit};
+use crate::graph::Graph;
+use crate::metrics::{find_cycles, Adjacency};
+use crate::parse::ParseCache;
+use crate::progress::{is_quiet, progress, set_quiet};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum HistoryFormat {
+    /// Comma-separated values, one row per commit
+    Csv,
+    /// JSON array, one object per commit
+    Json,
+}
+
+/// Summary statistics for the graph at one commit.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub commit: String,
+    pub date: String,
+    pub nodes: usize,
+    pub edges: usize,
+    pub cycles: usize,
+    pub external_packages: usize,
+}
+
+/// Generates the graph at commits between `from` and `to`, at most one every
+/// `interval_days`, and summarizes each.
+///
+/// Files are only parsed the first time their contents are seen, so later
+/// commits cost little more than listing their files.
+pub fn generate_history(
+    config: &Config,
+    from: Option<&str>,
+    to: &str,
+    interval_days: u32,
+) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+    let commits = list_commits(&config.path, from, to)?;
+    let commits = sample_commits(&commits, interval_days);
+    progress!("Analyzing {} commits...", commits.len());
+
+    let mut cache = ParseCache::default();
+    let mut entries = Vec::new();
+    for commit in commits {
+        progress!("  {} {}", &commit.id[..7], commit.date);
+
+        // Per-file progress for every commit would drown out the summary
+        let quiet = is_quiet();
+        set_quiet(true);
+        let graph = generate_graph_at_revision(config, &commit.id, &mut cache);
+        set_quiet(quiet);
+
+        entries.push(summarize_graph(commit, &graph?));
+    }
+
+    Ok(entries)
+}
+
+/// Picks the first commit, then each commit made at least `interval_days`
+/// after the previously picked one. The newest commit is always included.
+pub fn sample_commits(commits: &[Commit], interval_days: u32) -> Vec<&Commit> {
+    let interval = i64::from(interval_days) * SECONDS_PER_DAY;
+
+    let mut sampled: Vec<&Commit> = Vec::new();
+    for commit in commits {
+        match sampled.last() {
+            Some(last) if commit.timestamp - last.timestamp < interval => {}
+            _ => sampled.push(commit),
+        }
+    }
+
+    if let Some(newest) = commits.last() {
+        if sampled.last() != Some(&newest) {
+            sampled.push(newest);
+        }
+    }
+
+    sampled
+}
+
+fn summarize_graph(commit: &Commit, graph: &Graph) -> HistoryEntry {
+    HistoryEntry {
+        commit: commit.id.clone(),
+        date: commit.date.clone(),
+        nodes: graph.nodes.len(),
+        edges: graph.edges.len(),
+        cycles: find_cycles(&Adjacency::new(graph)).len(),
+        external_packages: external_packages(graph).len(),
+    }
+}
+
+pub fn render_history(entries: &[HistoryEntry], format: HistoryFormat) -> String {
+    match format {
+        HistoryFormat::Csv => render_csv(entries),
+        HistoryFormat::Json => serde_json::to_string_pretty(entries).unwrap() + "\n",
+    }
+}
+
+fn render_csv(entries: &[HistoryEntry]) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "commit,date,nodes,edges,cycles,external_packages").unwrap();
+    for entry in entries {
+        writeln!(
+            output,
+            "{},{},{},{},{},{}",
+            entry.commit,
+            entry.date,
+            entry.nodes,
+            entry.edges,
+            entry.cycles,
+            entry.external_packages
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, Node, NodeType, SCHEMA_VERSION};
+
+    fn commit(id: &str, day: i64) -> Commit {
+        Commit {
+            id: id.to_string(),
+            timestamp: day * SECONDS_PER_DAY,
+            date: format!("day {}", day),
+        }
+    }
+
+    #[test]
+    fn test_sample_commits() {
+        let commits = vec![
+            commit("a", 0),
+            commit("b", 3),
+            commit("c", 7),
+            commit("d", 8),
+            commit("e", 15),
+            commit("f", 16),
+        ];
+
+        let ids = |sampled: Vec<&Commit>| -> Vec<String> {
+            sampled.iter().map(|commit| commit.id.clone()).collect()
+        };
+
+        assert_eq!(ids(sample_commits(&commits, 7)), vec!["a", "c", "e", "f"]);
+        assert_eq!(ids(sample_commits(&commits, 0)).len(), commits.len());
+        assert_eq!(ids(sample_commits(&commits, 30)), vec!["a", "f"]);
+        assert!(sample_commits(&[], 7).is_empty());
+    }
+
+    #[test]
+    fn test_summarize_graph() {
+        let node = |id: &str, node_type: NodeType, label: &str| Node {
+            id: id.to_string(),
+            node_type,
+            label: label.to_string(),
+            path_absolute: "".to_string(),
+            path_relative: "".to_string(),
+            metrics: None,
+            size: None,
+        };
+        let edge = |source_id: &str, target_id: &str| Edge {
+            id: format!("{}-{}", source_id, target_id),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            weight: None,
+            kind: None,
+        };
+        let graph = Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: vec![
+                node("a", NodeType::Internal, "a"),
+                node("b", NodeType::Internal, "b"),
+                node("c", NodeType::External, "lodash"),
+                node("d", NodeType::External, "lodash/fp"),
+                node("e", NodeType::External, "react"),
+            ],
+            edges: vec![
+                edge("a", "b"),
+                edge("b", "a"),
+                edge("a", "c"),
+                edge("b", "d"),
+                edge("b", "e"),
+            ],
+        };
+
+        let entry = summarize_graph(&commit("abc", 1), &graph);
+
+        assert_eq!(
+            entry,
+            HistoryEntry {
+                commit: "abc".to_string(),
+                date: "day 1".to_string(),
+                nodes: 5,
+                edges: 5,
+                cycles: 1,
+                external_packages: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_history_csv() {
+        let entries = vec![HistoryEntry {
+            commit: "abc".to_string(),
+            date: "2024-01-01T00:00:00+00:00".to_string(),
+            nodes: 10,
+            edges: 12,
+            cycles: 1,
+            external_packages: 3,
+        }];
+
+        assert_eq!(
+            render_history(&entries, HistoryFormat::Csv),
+            "commit,date,nodes,edges,cycles,external_packages\nabc,2024-01-01T00:00:00+00:00,10,12,1,3\n"
+        );
+    }
+}
@@ -3,7 +3,7 @@ use crate::graph::Node;
 use std::collections::HashMap;
 use std::path::{Component, PathBuf};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UnresolvedImport {
     pub module_name: String,
 }
@@ -24,12 +24,17 @@ pub fn resolve_import(
     node: &Node,
     nodes_by_path: &HashMap<String, &Node>,
     module_resolution: &Option<Vec<ModuleResolutionItem>>,
+    extensions: &[String],
 ) -> ResolvedImport {
+    let unresolved_import = UnresolvedImport {
+        module_name: strip_query_and_fragment(&unresolved_import.module_name).to_string(),
+    };
+
     if unresolved_import.module_name.starts_with("./")
         || unresolved_import.module_name.starts_with("../")
     {
         let resolved_import =
-            resolve_relative_path_import(&unresolved_import, &node, &nodes_by_path);
+            resolve_relative_path_import(&unresolved_import, node, nodes_by_path, extensions);
 
         if let Some(resolved_import) = resolved_import {
             return resolved_import;
@@ -44,8 +49,9 @@ pub fn resolve_import(
             {
                 let resolved_import = resolve_resolution_with_substitution(
                     &unresolved_import,
-                    &nodes_by_path,
-                    &resolution_item,
+                    nodes_by_path,
+                    resolution_item,
+                    extensions,
                 );
 
                 if let Some(resolved_import) = resolved_import {
@@ -55,16 +61,30 @@ pub fn resolve_import(
         }
     }
 
-    let resolved_import = ResolvedImport::ExternalImport {
+    ResolvedImport::ExternalImport {
         target_module_name: unresolved_import.module_name,
-    };
-    return resolved_import;
+    }
+}
+
+/// Drops a `?query` or `#fragment` from an import, like in `./logo.svg?react`,
+/// which tells the bundler how to load the file rather than which file it is.
+/// Subpath imports like `#internal/utils` start with `#`, which is kept.
+fn strip_query_and_fragment(module_name: &str) -> &str {
+    let end = module_name
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| matches!(c, '?' | '#'))
+        .map(|(index, _)| index)
+        .unwrap_or(module_name.len());
+
+    &module_name[..end]
 }
 
 fn resolve_relative_path_import(
     unresolved_import: &UnresolvedImport,
     node: &Node,
     nodes_by_path: &HashMap<String, &Node>,
+    extensions: &[String],
 ) -> Option<ResolvedImport> {
     let mut resolved_path = PathBuf::from(&node.path_relative);
     resolved_path.pop();
@@ -88,7 +108,7 @@ fn resolve_relative_path_import(
         }
     }
 
-    let candidates = create_file_path_candidates(&resolved_path.to_str().unwrap());
+    let candidates = create_file_path_candidates(resolved_path.to_str().unwrap(), extensions);
     for candidate in candidates.iter() {
         let target_path = candidate.to_str().unwrap().to_string();
         match nodes_by_path.get(&target_path) {
@@ -113,12 +133,13 @@ fn resolve_resolution_with_substitution(
     unresolved_import: &UnresolvedImport,
     nodes_by_path: &HashMap<String, &Node>,
     resolution_item: &ModuleResolutionItem,
+    extensions: &[String],
 ) -> Option<ResolvedImport> {
     let relative_path = unresolved_import
         .module_name
         .replace(&resolution_item.pattern, &resolution_item.replacement);
 
-    let candidates = create_file_path_candidates(&relative_path);
+    let candidates = create_file_path_candidates(&relative_path, extensions);
     for candidate in candidates.iter() {
         let target_path = candidate.to_str().unwrap().to_string();
 
@@ -140,18 +161,21 @@ fn resolve_resolution_with_substitution(
     None
 }
 
-fn create_file_path_candidates(module_path: &str) -> Vec<PathBuf> {
-    vec![
-        PathBuf::from(module_path),
-        PathBuf::from(format!("{}.tsx", module_path)),
-        PathBuf::from(format!("{}.ts", module_path)),
-        PathBuf::from(format!("{}.jsx", module_path)),
-        PathBuf::from(format!("{}.js", module_path)),
-        PathBuf::from(format!("{}/index.tsx", module_path)),
-        PathBuf::from(format!("{}/index.ts", module_path)),
-        PathBuf::from(format!("{}/index.jsx", module_path)),
-        PathBuf::from(format!("{}/index.js", module_path)),
-    ]
+/// Lists the paths an import could refer to: the exact path, the path with
+/// each source extension, then an index file inside it with each extension.
+fn create_file_path_candidates(module_path: &str, extensions: &[String]) -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::from(module_path)];
+    for extension in extensions.iter() {
+        candidates.push(PathBuf::from(format!("{}.{}", module_path, extension)));
+    }
+    for extension in extensions.iter() {
+        candidates.push(PathBuf::from(format!(
+            "{}/index.{}",
+            module_path, extension
+        )));
+    }
+
+    candidates
 }
 
 #[cfg(test)]
@@ -159,6 +183,14 @@ mod test {
     use super::*;
 
     use crate::graph::NodeType;
+    use crate::language::typescript::DEFAULT_EXTENSIONS;
+
+    fn default_extensions() -> Vec<String> {
+        DEFAULT_EXTENSIONS
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect()
+    }
 
     #[test]
     fn test_resolve_import_both_root_directory() {
@@ -168,6 +200,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/myFile.tsx".to_string(),
             path_relative: "myFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let target_node = Node {
@@ -176,6 +210,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/myOtherFile.tsx".to_string(),
             path_relative: "myOtherFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let mut nodes_by_path = HashMap::new();
@@ -193,6 +229,7 @@ mod test {
             &source_node,
             &nodes_by_path,
             &module_resolution,
+            &default_extensions(),
         );
         assert_eq!(
             resolved_import,
@@ -211,6 +248,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/subdirectory/myFile.tsx".to_string(),
             path_relative: "subdirectory/myFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let target_node = Node {
@@ -219,6 +258,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/subdirectory/myOtherFile.tsx".to_string(),
             path_relative: "subdirectory/myOtherFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let mut nodes_by_path = HashMap::new();
@@ -236,6 +277,7 @@ mod test {
             &source_node,
             &nodes_by_path,
             &module_resolution,
+            &default_extensions(),
         );
         assert_eq!(
             resolved_import,
@@ -254,6 +296,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/feature-a/subfeature/myFile.tsx".to_string(),
             path_relative: "feature-a/subfeature/myFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let target_node = Node {
@@ -262,6 +306,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/feature-b/subfeature/myOtherFile.tsx".to_string(),
             path_relative: "feature-b/subfeature/myOtherFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let mut nodes_by_path = HashMap::new();
@@ -279,6 +325,7 @@ mod test {
             &source_node,
             &nodes_by_path,
             &module_resolution,
+            &default_extensions(),
         );
         assert_eq!(
             resolved_import,
@@ -297,6 +344,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/myFile.tsx".to_string(),
             path_relative: "myFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let mut nodes_by_path = HashMap::new();
@@ -313,6 +362,7 @@ mod test {
             &source_node,
             &nodes_by_path,
             &module_resolution,
+            &default_extensions(),
         );
         assert_eq!(
             resolved_import,
@@ -322,6 +372,64 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_resolve_import_with_query() {
+        let source_node = Node {
+            id: "7c00c281-b165-4ccd-9deb-3575a0d7e71d".to_string(),
+            node_type: NodeType::Internal,
+            label: "test".to_string(),
+            path_absolute: "/path/to/repo/src/App.tsx".to_string(),
+            path_relative: "src/App.tsx".to_string(),
+            metrics: None,
+            size: None,
+        };
+
+        let target_node = Node {
+            id: "c319b51d-2f58-41aa-96f5-fbe35bb227b4".to_string(),
+            node_type: NodeType::Asset,
+            label: "test".to_string(),
+            path_absolute: "/path/to/repo/src/logo.svg".to_string(),
+            path_relative: "src/logo.svg".to_string(),
+            metrics: None,
+            size: None,
+        };
+
+        let mut nodes_by_path = HashMap::new();
+        nodes_by_path.insert(source_node.path_relative.clone(), &source_node);
+        nodes_by_path.insert(target_node.path_relative.clone(), &target_node);
+
+        for module_name in ["./logo.svg?react", "./logo.svg#icon"] {
+            let unresolved_import = UnresolvedImport {
+                module_name: module_name.to_string(),
+            };
+
+            let resolved_import = resolve_import(
+                unresolved_import,
+                &source_node,
+                &nodes_by_path,
+                &None,
+                &default_extensions(),
+            );
+            assert_eq!(
+                resolved_import,
+                ResolvedImport::InternalImport {
+                    target_path: "src/logo.svg".to_string(),
+                    target_node_id: "c319b51d-2f58-41aa-96f5-fbe35bb227b4".to_string()
+                }
+            );
+        }
+
+        assert_eq!(
+            strip_query_and_fragment("#internal/utils"),
+            "#internal/utils"
+        );
+        assert_eq!(
+            strip_query_and_fragment("#internal/utils?raw"),
+            "#internal/utils"
+        );
+        assert_eq!(strip_query_and_fragment(""), "");
+    }
+
     #[test]
     fn resolve_imports_with_substitution() {
         let source_node = Node {
@@ -330,6 +438,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/feature-a/subfeature/myFile.tsx".to_string(),
             path_relative: "feature-a/subfeature/myFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let target_node = Node {
@@ -338,6 +448,8 @@ mod test {
             label: "test".to_string(),
             path_absolute: "/path/to/repo/app/common/myCommonFile.tsx".to_string(),
             path_relative: "app/common/myCommonFile.tsx".to_string(),
+            metrics: None,
+            size: None,
         };
 
         let mut nodes_by_path = HashMap::new();
@@ -358,6 +470,7 @@ mod test {
             &source_node,
             &nodes_by_path,
             &module_resolution,
+            &default_extensions(),
         );
         assert_eq!(
             resolved_import,
@@ -367,4 +480,20 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_create_file_path_candidates() {
+        let extensions = vec!["ts".to_string(), "mjs".to_string()];
+
+        assert_eq!(
+            create_file_path_candidates("src/util", &extensions),
+            vec![
+                PathBuf::from("src/util"),
+                PathBuf::from("src/util.ts"),
+                PathBuf::from("src/util.mjs"),
+                PathBuf::from("src/util/index.ts"),
+                PathBuf::from("src/util/index.mjs"),
+            ]
+        );
+    }
 }
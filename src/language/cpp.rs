@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use tree_sitter::{Node as SyntaxNode, Parser};
+
+use super::{configured_extensions, LanguageBackend};
+use crate::config::Config;
+use crate::graph::Node;
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::{node_text, ParsedFile};
+use crate::source::{normalize_path, SourceTree};
+
+const DEFAULT_EXTENSIONS: [&str; 8] = ["c", "h", "cc", "cpp", "cxx", "hpp", "hh", "hxx"];
+
+/// Compiler flags that add an include directory, e.g. `-Iinclude` or `-I include`.
+const INCLUDE_FLAGS: [&str; 3] = ["-I", "-iquote", "-isystem"];
+
+/// C and C++, with one node per source or header file.
+///
+/// Includes are recorded as written between the quotes, or with the angle
+/// brackets kept for `#include <...>`. Quoted includes are looked up next to
+/// the including file first, then in the include directories; angle-bracket
+/// includes only in the include directories. Headers that aren't found, like
+/// the system ones, are external.
+pub struct CppBackend {
+    extensions: Vec<String>,
+    /// Directories to look up includes in, relative to the project root
+    include_directories: Vec<PathBuf>,
+}
+
+impl CppBackend {
+    pub fn new(config: &Config, source: &dyn SourceTree) -> Result<Self, Box<dyn Error>> {
+        let extensions = configured_extensions(config, &DEFAULT_EXTENSIONS);
+
+        // Absolute, to compare with the absolute directories in compile_commands.json
+        let project_directory = fs::canonicalize(&config.path)
+            .map_err(|error| format!("Couldn't read {}: {}", config.path, error))?;
+        let project_directory = project_directory.as_path();
+        let mut include_directories: Vec<PathBuf> = config
+            .include_directories
+            .iter()
+            .flatten()
+            .map(|directory| normalize_path(Path::new(directory)))
+            .collect();
+
+        let compile_commands_path = match &config.compile_commands {
+            Some(compile_commands_path) => compile_commands_path.as_str(),
+            None => "compile_commands.json",
+        };
+        let compile_commands =
+            read_compile_commands(source, project_directory, compile_commands_path).map_err(
+                |error| {
+                    format!(
+                        "Couldn't read {}: {}",
+                        project_directory.join(compile_commands_path).display(),
+                        error
+                    )
+                },
+            );
+        let compile_commands = match compile_commands {
+            Ok(compile_commands) => Some(compile_commands),
+            // Only a database named in the config has to exist
+            Err(_) if config.compile_commands.is_none() => None,
+            Err(error) => return Err(error.into()),
+        };
+        if let Some(compile_commands) = compile_commands {
+            for directory in read_include_directories(&compile_commands, project_directory)? {
+                if !include_directories.contains(&directory) {
+                    include_directories.push(directory);
+                }
+            }
+        }
+
+        Ok(CppBackend {
+            extensions,
+            include_directories,
+        })
+    }
+}
+
+impl LanguageBackend for CppBackend {
+    fn source_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    fn parse_file(&self, _path_relative: &str, file_contents: &str) -> ParsedFile {
+        let mut parser = Parser::new();
+        parser.set_language(tree_sitter_cpp::language()).unwrap();
+        let tree = parser.parse(file_contents, None).unwrap();
+
+        let mut imports = vec![];
+        collect_includes(tree.root_node(), file_contents, &mut imports);
+
+        // Macros trip up the grammar often enough that its syntax errors
+        // would mostly be noise, and includes are read fine regardless
+        ParsedFile {
+            imports,
+            warnings: vec![],
+            package: None,
+        }
+    }
+
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport> {
+        let module_name = import.module_name;
+        let (header, is_system) = match module_name
+            .strip_prefix('<')
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            Some(header) => (header, true),
+            None => (module_name.as_str(), false),
+        };
+
+        let mut directories = vec![];
+        if !is_system {
+            directories.push(Path::new(&node.path_relative).parent()?.to_path_buf());
+        }
+        directories.extend(self.include_directories.iter().cloned());
+
+        for directory in directories {
+            let candidate = normalize_path(&directory.join(header));
+            let candidate = candidate.to_str().unwrap();
+            if let Some(target_node) = nodes_by_path.get(candidate) {
+                if target_node.id == node.id {
+                    return None;
+                }
+                return Some(ResolvedImport::InternalImport {
+                    target_path: candidate.to_string(),
+                    target_node_id: target_node.id.clone(),
+                });
+            }
+        }
+
+        Some(ResolvedImport::ExternalImport {
+            target_module_name: header.to_string(),
+        })
+    }
+}
+
+fn collect_includes(node: SyntaxNode, file_contents: &str, imports: &mut Vec<UnresolvedImport>) {
+    if node.kind() == "preproc_include" {
+        let path = node.child_by_field_name("path").unwrap();
+        let module_name = match path.kind() {
+            "string_literal" => node_text(path, file_contents).trim_matches('"').to_string(),
+            "system_lib_string" => node_text(path, file_contents),
+            // A macro, like `#include HEADER`, which only the preprocessor knows
+            _ => return,
+        };
+        if !imports
+            .iter()
+            .any(|import| import.module_name == module_name)
+        {
+            imports.push(UnresolvedImport { module_name });
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_includes(child, file_contents, imports);
+    }
+}
+
+/// Reads a compilation database from the analyzed files, or from disk if it
+/// isn't among them, since it's usually generated by the build rather than
+/// committed, or kept outside the project.
+fn read_compile_commands(
+    source: &dyn SourceTree,
+    project_directory: &Path,
+    compile_commands_path: &str,
+) -> io::Result<String> {
+    let path = Path::new(compile_commands_path);
+    let is_inside_project = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if is_inside_project {
+        match source.read_file(normalize_path(path).to_str().unwrap()) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            result => return result,
+        }
+    }
+
+    fs::read_to_string(project_directory.join(path))
+}
+
+/// Collects the include directories passed to the compiler in a
+/// `compile_commands.json` compilation database, keeping the ones inside the
+/// project, relative to it. The project directory has to be absolute.
+fn read_include_directories(
+    compile_commands: &str,
+    project_directory: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(compile_commands)?;
+
+    let mut include_directories = vec![];
+    for entry in entries.iter() {
+        let arguments: Vec<String> = match (entry.get("arguments"), entry.get("command")) {
+            (Some(arguments), _) => arguments
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|argument| argument.as_str())
+                .map(|argument| argument.to_string())
+                .collect(),
+            (None, Some(command)) => command
+                .as_str()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(|argument| argument.to_string())
+                .collect(),
+            (None, None) => continue,
+        };
+        let entry_directory = entry
+            .get("directory")
+            .and_then(|directory| directory.as_str())
+            .map(Path::new)
+            .unwrap_or(project_directory);
+
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            let Some(flag) = INCLUDE_FLAGS
+                .iter()
+                .find(|flag| argument.starts_with(**flag))
+            else {
+                continue;
+            };
+            let directory = match &argument[flag.len()..] {
+                "" => match arguments.next() {
+                    Some(directory) => directory.as_str(),
+                    None => break,
+                },
+                directory => directory,
+            };
+
+            let directory = normalize_path(&entry_directory.join(directory));
+            let Ok(directory) = directory.strip_prefix(normalize_path(project_directory)) else {
+                continue;
+            };
+            if !include_directories.iter().any(|known| known == directory) {
+                include_directories.push(directory.to_path_buf());
+            }
+        }
+    }
+
+    Ok(include_directories)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::NodeType;
+    use crate::language::{resolve_test_import, test_nodes};
+    use crate::source::WorkingDirectory;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parse_file() {
+        let file_contents = r#"#include <stdio.h>
+#include <vector>
+#include "util/strings.h"
+#include HEADER
+
+#ifdef _WIN32
+#  include "platform/win32.h"
+#endif
+
+#include "util/strings.h"
+"#;
+        let backend = CppBackend {
+            extensions: vec!["c".to_string(), "h".to_string()],
+            include_directories: vec![],
+        };
+
+        let module_names: Vec<String> = backend
+            .parse_file("src/main.c", file_contents)
+            .imports
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect();
+
+        assert_eq!(
+            module_names,
+            vec![
+                "<stdio.h>",
+                "<vector>",
+                "util/strings.h",
+                "platform/win32.h"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let paths = [
+            "src/main.c",
+            "src/util/strings.h",
+            "include/app/config.h",
+            "src/config.h",
+        ];
+        let nodes = test_nodes(&paths, |_| NodeType::Internal);
+
+        let backend = CppBackend {
+            extensions: vec!["c".to_string(), "h".to_string()],
+            include_directories: vec![PathBuf::from("include"), PathBuf::from("src")],
+        };
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(resolve(0, "util/strings.h"), some("src/util/strings.h"));
+        assert_eq!(resolve(1, "../config.h"), some("src/config.h"));
+        assert_eq!(resolve(1, "app/config.h"), some("include/app/config.h"));
+        assert_eq!(resolve(0, "<app/config.h>"), some("include/app/config.h"));
+        assert_eq!(resolve(1, "<config.h>"), some("src/config.h"));
+        assert_eq!(resolve(1, "<strings.h>"), some("external strings.h"));
+        assert_eq!(resolve(0, "generated.h"), some("external generated.h"));
+    }
+
+    #[test]
+    fn test_read_include_directories() {
+        let compile_commands = r#"[
+  {
+    "directory": "/repo/build",
+    "command": "cc -I../include -I /repo/third_party/zlib -isystem /usr/include -c ../src/main.c",
+    "file": "../src/main.c"
+  },
+  {
+    "directory": "/repo",
+    "arguments": ["c++", "-Iinclude", "-iquote", "src", "-c", "src/app.cpp"],
+    "file": "src/app.cpp"
+  }
+]"#;
+
+        let include_directories =
+            read_include_directories(compile_commands, Path::new("/repo")).unwrap();
+
+        assert_eq!(
+            include_directories,
+            vec![
+                PathBuf::from("include"),
+                PathBuf::from("third_party/zlib"),
+                PathBuf::from("src"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_include_directories_with_relative_project_path() {
+        let root = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("include")).unwrap();
+        let root = fs::canonicalize(root).unwrap();
+        let compile_commands = format!(
+            r#"[{{"directory": "{}", "command": "cc -Iinclude -c src/main.c"}}]"#,
+            root.display()
+        );
+        fs::write(root.join("compile_commands.json"), compile_commands).unwrap();
+
+        // The config's path is usually relative to where depdoc runs
+        let current_directory = fs::canonicalize(std::env::current_dir().unwrap()).unwrap();
+        let mut path = PathBuf::new();
+        for _ in current_directory.ancestors().skip(1) {
+            path.push("..");
+        }
+        let path = path.join(root.strip_prefix("/").unwrap());
+        let config: Config = serde_yaml::from_str(&format!(
+            "title: test\nlanguage: c\npath: {}\n",
+            path.display()
+        ))
+        .unwrap();
+
+        let backend = CppBackend::new(&config, &WorkingDirectory::new(&config.path)).unwrap();
+
+        assert_eq!(backend.include_directories, vec![PathBuf::from("include")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use tree_sitter::{Node as SyntaxNode, Parser};
+
+use super::{configured_extensions, LanguageBackend};
+use crate::config::Config;
+use crate::graph::{Node, NodeType};
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::{find_syntax_errors, node_text, ParsedFile};
+use crate::source::SourceTree;
+
+/// Go, with one node per package, i.e. per directory.
+///
+/// Imports are recorded as the import paths written in the file. Paths under
+/// the module path from `go.mod` map to the package directories below the
+/// project root; everything else is external. Like the `go` command, files in
+/// `vendor` and `testdata` directories aren't analyzed.
+pub struct GoBackend {
+    extensions: Vec<String>,
+    /// The `module` path from go.mod, e.g. `example.com/app`
+    module_path: String,
+    /// Module paths of the `require` directives in go.mod
+    required_modules: Vec<String>,
+}
+
+impl GoBackend {
+    pub fn new(config: &Config, source: &dyn SourceTree) -> Result<Self, Box<dyn Error>> {
+        let extensions = configured_extensions(config, &["go"]);
+
+        let go_mod_path = Path::new(&config.path).join("go.mod");
+        let go_mod = source
+            .read_file("go.mod")
+            .map_err(|error| format!("Couldn't read {}: {}", go_mod_path.display(), error))?;
+        let GoMod {
+            module_path,
+            required_modules,
+        } = parse_go_mod(&go_mod)
+            .ok_or(format!("No module directive in {}", go_mod_path.display()))?;
+
+        Ok(GoBackend {
+            extensions,
+            module_path,
+            required_modules,
+        })
+    }
+}
+
+impl LanguageBackend for GoBackend {
+    fn source_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    fn skipped_directories(&self) -> &[&str] {
+        &["vendor", "testdata"]
+    }
+
+    fn node_path(&self, path_relative: &str, _package: Option<&str>) -> String {
+        match Path::new(path_relative).parent() {
+            Some(directory) if directory != Path::new("") => {
+                directory.to_str().unwrap().to_string()
+            }
+            _ => ".".to_string(),
+        }
+    }
+
+    fn parse_file(&self, _path_relative: &str, file_contents: &str) -> ParsedFile {
+        let mut parser = Parser::new();
+        parser.set_language(tree_sitter_go::language()).unwrap();
+        let tree = parser.parse(file_contents, None).unwrap();
+
+        let mut imports = vec![];
+        collect_imports(tree.root_node(), file_contents, &mut imports);
+
+        ParsedFile {
+            imports,
+            warnings: find_syntax_errors(&tree, file_contents),
+            package: None,
+        }
+    }
+
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport> {
+        let import_path = import.module_name;
+
+        let package_directory = if import_path == self.module_path {
+            Some(".")
+        } else {
+            import_path
+                .strip_prefix(&self.module_path)
+                .and_then(|rest| rest.strip_prefix('/'))
+        };
+        if let Some(package_directory) = package_directory {
+            // A package without any files to analyze, e.g. only tests
+            let target_node = nodes_by_path.get(package_directory)?;
+            if target_node.id == node.id {
+                return None;
+            }
+            return Some(ResolvedImport::InternalImport {
+                target_path: package_directory.to_string(),
+                target_node_id: target_node.id.clone(),
+            });
+        }
+
+        if is_stdlib_package(&import_path) {
+            return Some(ResolvedImport::ExternalImport {
+                target_module_name: import_path,
+            });
+        }
+
+        // Packages of a required module make one node for the module
+        let required_module = self
+            .required_modules
+            .iter()
+            .filter(|module| is_package_of(&import_path, module))
+            .max_by_key(|module| module.len());
+
+        Some(ResolvedImport::ExternalImport {
+            target_module_name: required_module.cloned().unwrap_or(import_path),
+        })
+    }
+
+    fn external_node_type(&self, module_name: &str) -> NodeType {
+        if is_stdlib_package(module_name) {
+            NodeType::Stdlib
+        } else {
+            NodeType::External
+        }
+    }
+}
+
+/// Standard library import paths are the only ones whose first element has no dot.
+fn is_stdlib_package(import_path: &str) -> bool {
+    !import_path.split('/').next().unwrap().contains('.')
+}
+
+fn is_package_of(import_path: &str, module_path: &str) -> bool {
+    import_path == module_path
+        || import_path
+            .strip_prefix(module_path)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn collect_imports(node: SyntaxNode, file_contents: &str, imports: &mut Vec<UnresolvedImport>) {
+    if node.kind() == "import_spec" {
+        let path = node_text(node.child_by_field_name("path").unwrap(), file_contents);
+        let module_name = path.trim_matches(|c| c == '"' || c == '`').to_string();
+        if !imports
+            .iter()
+            .any(|import| import.module_name == module_name)
+        {
+            imports.push(UnresolvedImport { module_name });
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_imports(child, file_contents, imports);
+    }
+}
+
+struct GoMod {
+    module_path: String,
+    required_modules: Vec<String>,
+}
+
+/// Reads the `module` and `require` directives of a go.mod file, in both the
+/// single-line and the parenthesized block forms.
+fn parse_go_mod(go_mod: &str) -> Option<GoMod> {
+    let mut module_path = None;
+    let mut required_modules = vec![];
+    let mut block: Option<&str> = None;
+
+    for line in go_mod.lines() {
+        let line = match line.find("//") {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+        let mut words = line.split_whitespace();
+        let Some(first) = words.next() else {
+            continue;
+        };
+
+        let (directive, argument) = match block {
+            Some(_) if first == ")" => {
+                block = None;
+                continue;
+            }
+            Some(directive) => (directive, Some(first)),
+            None => match words.next() {
+                Some("(") => {
+                    block = Some(first);
+                    continue;
+                }
+                argument => (first, argument),
+            },
+        };
+        let Some(argument) = argument.map(|argument| argument.trim_matches('"')) else {
+            continue;
+        };
+
+        match directive {
+            "module" => module_path = Some(argument.to_string()),
+            "require" => required_modules.push(argument.to_string()),
+            _ => {}
+        }
+    }
+
+    Some(GoMod {
+        module_path: module_path?,
+        required_modules,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::language::{resolve_test_import, test_nodes};
+
+    fn backend() -> GoBackend {
+        GoBackend {
+            extensions: vec!["go".to_string()],
+            module_path: "example.com/app".to_string(),
+            required_modules: vec![
+                "github.com/pkg/errors".to_string(),
+                "golang.org/x/sync".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_go_mod() {
+        let go_mod = r#"module "example.com/app" // the app
+
+go 1.21
+
+require github.com/pkg/errors v0.9.1
+
+require (
+	golang.org/x/sync v0.5.0
+	// indirect dependencies
+	golang.org/x/text v0.14.0 // indirect
+)
+
+replace golang.org/x/text => ../text
+"#;
+
+        let go_mod = parse_go_mod(go_mod).unwrap();
+
+        assert_eq!(go_mod.module_path, "example.com/app");
+        assert_eq!(
+            go_mod.required_modules,
+            vec![
+                "github.com/pkg/errors",
+                "golang.org/x/sync",
+                "golang.org/x/text"
+            ]
+        );
+        assert!(parse_go_mod("go 1.21\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let file_contents = r#"package main
+
+import "fmt"
+
+import (
+	"net/http"
+	errs "github.com/pkg/errors"
+	_ "example.com/app/internal/db"
+	. `example.com/app/util`
+	"fmt"
+)
+"#;
+
+        let module_names: Vec<String> = backend()
+            .parse_file("main.go", file_contents)
+            .imports
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect();
+
+        assert_eq!(
+            module_names,
+            vec![
+                "fmt",
+                "net/http",
+                "github.com/pkg/errors",
+                "example.com/app/internal/db",
+                "example.com/app/util",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_node_path() {
+        let backend = backend();
+
+        assert_eq!(backend.node_path("main.go", None), ".");
+        assert_eq!(backend.node_path("internal/db/db.go", None), "internal/db");
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let paths = [".", "internal/db", "util"];
+        let nodes = test_nodes(&paths, |_| NodeType::Internal);
+
+        let backend = backend();
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(
+            resolve(0, "example.com/app/internal/db"),
+            some("internal/db")
+        );
+        assert_eq!(resolve(1, "example.com/app"), some("."));
+        assert_eq!(resolve(1, "example.com/app/internal/db"), None);
+        assert_eq!(resolve(0, "example.com/app/missing"), None);
+        assert_eq!(resolve(0, "net/http"), some("external net/http"));
+        assert_eq!(
+            resolve(0, "golang.org/x/sync/errgroup"),
+            some("external golang.org/x/sync")
+        );
+        assert_eq!(
+            resolve(0, "example.com/other/pkg"),
+            some("external example.com/other/pkg")
+        );
+
+        assert!(matches!(
+            backend.external_node_type("net/http"),
+            NodeType::Stdlib
+        ));
+        assert!(matches!(
+            backend.external_node_type("golang.org/x/sync"),
+            NodeType::External
+        ));
+    }
+}
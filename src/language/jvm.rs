@@ -0,0 +1,592 @@
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tree_sitter::{Node as SyntaxNode, Parser, Tree};
+
+use super::{configured_extensions, LanguageBackend};
+use crate::config::Config;
+use crate::graph::{Node, NodeType};
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::{file_extension, find_syntax_errors, node_text, ParsedFile};
+use crate::source::normalize_path;
+
+/// The usual Maven and Gradle directories that packages start from.
+const DEFAULT_SOURCE_ROOTS: [&str; 2] = ["src/main/java", "src/main/kotlin"];
+
+/// Top-level packages of the Java and Kotlin standard libraries.
+const STDLIB_PACKAGES: [&str; 5] = ["java", "javax", "jdk", "sun", "kotlin"];
+
+/// Java and Kotlin, with one node per source file, or per package when the
+/// config asks for packages.
+///
+/// Imports are recorded as written, like `com.example.User`, with a trailing
+/// `.*` for wildcard imports. Resolution follows the usual layout where
+/// `com.example.User` lives in `com/example/User.java` or `User.kt` under a
+/// source root. A wildcard import of a package can't be pinned to one file,
+/// so it only makes an edge in the package-level graph, and neither can a
+/// Kotlin import of a top-level function like `com.example.util.slugify`.
+pub struct JvmBackend {
+    extensions: Vec<String>,
+    /// Directories packages start from, relative to the project root or to a
+    /// module inside it, e.g. `src/main/java` also matches `app/src/main/java`
+    source_roots: Vec<PathBuf>,
+    packages: bool,
+    /// The directories matching `source_roots` that hold source files, found
+    /// from the nodes when the first import is resolved
+    found_source_roots: OnceCell<Vec<PathBuf>>,
+    /// The directories that hold source files, found from the nodes when the
+    /// first import is resolved
+    source_directories: OnceCell<HashSet<PathBuf>>,
+}
+
+impl JvmBackend {
+    pub fn new(config: &Config) -> Self {
+        let extensions = configured_extensions(config, &["java", "kt"]);
+        let source_roots = match &config.source_roots {
+            Some(source_roots) => source_roots.iter().map(String::as_str).collect(),
+            None => DEFAULT_SOURCE_ROOTS.to_vec(),
+        };
+
+        JvmBackend {
+            extensions,
+            source_roots: source_roots
+                .into_iter()
+                .map(|source_root| normalize_path(Path::new(source_root)))
+                .collect(),
+            packages: config.packages,
+            found_source_roots: OnceCell::new(),
+            source_directories: OnceCell::new(),
+        }
+    }
+
+    fn found_source_roots(&self, nodes_by_path: &HashMap<String, &Node>) -> &[PathBuf] {
+        self.found_source_roots.get_or_init(|| {
+            let mut found_source_roots = vec![];
+            for path in nodes_by_path.keys() {
+                let source_root = Path::new(path)
+                    .ancestors()
+                    .find(|ancestor| self.is_source_root(ancestor));
+                if let Some(source_root) = source_root {
+                    if !found_source_roots.iter().any(|found| found == source_root) {
+                        found_source_roots.push(source_root.to_path_buf());
+                    }
+                }
+            }
+            found_source_roots.sort();
+            found_source_roots
+        })
+    }
+
+    fn source_directories(&self, nodes_by_path: &HashMap<String, &Node>) -> &HashSet<PathBuf> {
+        self.source_directories.get_or_init(|| {
+            nodes_by_path
+                .keys()
+                .filter_map(|path| Path::new(path).parent())
+                .map(Path::to_path_buf)
+                .collect()
+        })
+    }
+
+    fn is_source_root(&self, directory: &Path) -> bool {
+        self.source_roots.iter().any(|source_root| {
+            if source_root.as_os_str().is_empty() {
+                directory.as_os_str().is_empty()
+            } else {
+                directory.ends_with(source_root)
+            }
+        })
+    }
+
+    /// Finds the file that declares a class, trying the longest prefix of
+    /// the name first, since the rest may name nested classes or members.
+    fn find_class_file(
+        &self,
+        segments: &[&str],
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<String> {
+        for source_root in self.found_source_roots(nodes_by_path) {
+            for length in (1..=segments.len()).rev() {
+                // Class names start with a capital by convention, which tells
+                // them apart from a package directory with the same name
+                if !segments[length - 1].starts_with(char::is_uppercase) {
+                    continue;
+                }
+                let class_path = source_root.join(segments[..length].join("/"));
+                for extension in self.extensions.iter() {
+                    let candidate = class_path.with_extension(extension);
+                    let candidate = candidate.to_str().unwrap();
+                    if nodes_by_path.contains_key(candidate) {
+                        return Some(candidate.to_string());
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl LanguageBackend for JvmBackend {
+    fn source_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    /// In the package-level graph, the path of the package the file declares,
+    /// like `com/example/model`, so that a package split across
+    /// `src/main/java` and `src/main/kotlin` makes one node. Files without a
+    /// `package` declaration go by their directory under the source root.
+    fn node_path(&self, path_relative: &str, package: Option<&str>) -> String {
+        if !self.packages {
+            return path_relative.to_string();
+        }
+
+        // Kotlin files needn't be in the directory matching their package
+        if let Some(package) = package {
+            return package.replace('.', "/");
+        }
+
+        let directory = Path::new(path_relative).parent().unwrap();
+        let package_path = directory
+            .ancestors()
+            .find(|ancestor| self.is_source_root(ancestor))
+            .map(|source_root| directory.strip_prefix(source_root).unwrap())
+            .unwrap_or(directory);
+        match package_path.to_str().unwrap() {
+            "" => ".".to_string(),
+            package_path => package_path.to_string(),
+        }
+    }
+
+    fn parse_file(&self, path_relative: &str, file_contents: &str) -> ParsedFile {
+        if is_kotlin_file(path_relative) {
+            let header = parse_kotlin_header(file_contents);
+            return ParsedFile {
+                imports: header.imports,
+                warnings: vec![],
+                package: header.package,
+            };
+        }
+
+        let tree = parse_java(file_contents);
+
+        let mut imports = vec![];
+        collect_java_imports(tree.root_node(), file_contents, &mut imports);
+
+        ParsedFile {
+            imports,
+            warnings: find_syntax_errors(&tree, file_contents),
+            package: read_java_package(&tree, file_contents),
+        }
+    }
+
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport> {
+        let is_wildcard = import.module_name.ends_with(".*");
+        let name = import.module_name.trim_end_matches(".*");
+        let segments: Vec<&str> = name.split('.').collect();
+        let mut package_segments: Vec<&str> = segments
+            .iter()
+            .take_while(|segment| !segment.starts_with(char::is_uppercase))
+            .cloned()
+            .collect();
+        if !is_wildcard && package_segments.len() == segments.len() {
+            // No class in the name, so it ends with a top-level function or
+            // property, like Kotlin's `import com.example.util.slugify`
+            package_segments.pop();
+        }
+        if package_segments.is_empty() {
+            return None;
+        }
+
+        let target_path = if self.packages {
+            package_segments.join("/")
+        } else {
+            match self.find_class_file(&segments, nodes_by_path) {
+                Some(class_file) => class_file,
+                None => {
+                    // A wildcard or top-level function import from one of this
+                    // project's packages doesn't name a file, so it only
+                    // counts between packages
+                    let source_directories = self.source_directories(nodes_by_path);
+                    let is_internal_package =
+                        self.found_source_roots(nodes_by_path)
+                            .iter()
+                            .any(|source_root| {
+                                source_directories
+                                    .contains(&source_root.join(package_segments.join("/")))
+                            });
+                    if is_internal_package {
+                        return None;
+                    }
+                    String::new()
+                }
+            }
+        };
+
+        if let Some(target_node) = nodes_by_path.get(&target_path) {
+            if target_node.id == node.id {
+                return None;
+            }
+            return Some(ResolvedImport::InternalImport {
+                target_path,
+                target_node_id: target_node.id.clone(),
+            });
+        }
+
+        Some(ResolvedImport::ExternalImport {
+            target_module_name: package_segments.join("."),
+        })
+    }
+
+    fn external_node_type(&self, module_name: &str) -> NodeType {
+        let top_level_package = module_name.split('.').next().unwrap();
+        if STDLIB_PACKAGES.contains(&top_level_package) {
+            NodeType::Stdlib
+        } else {
+            NodeType::External
+        }
+    }
+}
+
+fn is_kotlin_file(path_relative: &str) -> bool {
+    matches!(file_extension(path_relative).as_deref(), Some("kt" | "kts"))
+}
+
+fn parse_java(file_contents: &str) -> Tree {
+    let mut parser = Parser::new();
+    parser.set_language(tree_sitter_java::language()).unwrap();
+    parser.parse(file_contents, None).unwrap()
+}
+
+/// Reads the name of the package a file declares, like `com.example.model`.
+fn read_java_package(tree: &Tree, file_contents: &str) -> Option<String> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let package_declaration = root
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "package_declaration")?;
+    let mut cursor = package_declaration.walk();
+    let name = package_declaration
+        .named_children(&mut cursor)
+        .find(|child| matches!(child.kind(), "identifier" | "scoped_identifier"))?;
+
+    Some(node_text(name, file_contents))
+}
+
+fn collect_java_imports(
+    node: SyntaxNode,
+    file_contents: &str,
+    imports: &mut Vec<UnresolvedImport>,
+) {
+    if node.kind() == "import_declaration" {
+        let mut name = None;
+        let mut is_wildcard = false;
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "identifier" | "scoped_identifier" => name = Some(node_text(child, file_contents)),
+                "asterisk" => is_wildcard = true,
+                _ => {}
+            }
+        }
+        if let Some(name) = name {
+            add_import(imports, name, is_wildcard);
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_java_imports(child, file_contents, imports);
+    }
+}
+
+/// The `package` and `import` directives at the top of a Kotlin file.
+struct KotlinHeader {
+    package: Option<String>,
+    imports: Vec<UnresolvedImport>,
+}
+
+/// Reads the header of a Kotlin file line by line, as there's no Kotlin
+/// grammar to parse it with. Imports have to come before any declarations,
+/// so this stops at the first line that isn't a comment, an annotation, or
+/// a `package` or `import` directive.
+fn parse_kotlin_header(file_contents: &str) -> KotlinHeader {
+    let mut package = None;
+    let mut imports = vec![];
+    let mut in_block_comment = false;
+
+    for line in file_contents.lines() {
+        let mut line = line.trim();
+        if in_block_comment {
+            match line.find("*/") {
+                Some(comment_end) => {
+                    in_block_comment = false;
+                    line = line[comment_end + 2..].trim();
+                }
+                None => continue,
+            }
+        }
+        if let Some(comment_start) = line.find("/*") {
+            in_block_comment = !line[comment_start..].contains("*/");
+            line = line[..comment_start].trim();
+        }
+        if let Some(comment_start) = line.find("//") {
+            line = line[..comment_start].trim();
+        }
+
+        if line.is_empty() || line.starts_with('@') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("package ") {
+            package = Some(name.trim_end_matches(';').trim().replace('`', ""));
+            continue;
+        }
+        let Some(import) = line.strip_prefix("import ") else {
+            break;
+        };
+
+        // `import a.b.C as D`, optionally followed by a semicolon
+        let name = import
+            .split(|c: char| c.is_whitespace() || c == ';')
+            .next()
+            .unwrap()
+            .replace('`', "");
+        match name.strip_suffix(".*") {
+            Some(name) => add_import(&mut imports, name.to_string(), true),
+            None => add_import(&mut imports, name, false),
+        }
+    }
+
+    KotlinHeader { package, imports }
+}
+
+fn add_import(imports: &mut Vec<UnresolvedImport>, name: String, is_wildcard: bool) {
+    let module_name = match is_wildcard {
+        true => format!("{}.*", name),
+        false => name,
+    };
+    if !imports
+        .iter()
+        .any(|import| import.module_name == module_name)
+    {
+        imports.push(UnresolvedImport { module_name });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::language::{resolve_test_import, test_nodes};
+
+    fn backend(packages: bool) -> JvmBackend {
+        JvmBackend {
+            extensions: vec!["java".to_string(), "kt".to_string()],
+            source_roots: DEFAULT_SOURCE_ROOTS.iter().map(PathBuf::from).collect(),
+            packages,
+            found_source_roots: OnceCell::new(),
+            source_directories: OnceCell::new(),
+        }
+    }
+
+    fn node_paths(backend: &JvmBackend, files: &[(&str, &str)]) -> Vec<String> {
+        files
+            .iter()
+            .map(|(path, file_contents)| {
+                let package = backend.parse_file(path, file_contents).package;
+                backend.node_path(path, package.as_deref())
+            })
+            .collect()
+    }
+
+    fn module_names(parsed_file: ParsedFile) -> Vec<String> {
+        parsed_file
+            .imports
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_java_file() {
+        let file_contents = r#"package com.example.web;
+
+import java.util.List;
+import static com.example.util.Strings.join;
+import com.example.model.*;
+import com.example.model.User;
+
+public class UserController {}
+"#;
+
+        let parsed_file = backend(false).parse_file("A.java", file_contents);
+
+        assert_eq!(parsed_file.package, Some("com.example.web".to_string()));
+        assert_eq!(
+            module_names(parsed_file),
+            vec![
+                "java.util.List",
+                "com.example.util.Strings.join",
+                "com.example.model.*",
+                "com.example.model.User",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_kotlin_file() {
+        let file_contents = r#"/*
+ * import fake.Import
+ */
+@file:JvmName("Users")
+package com.example.web
+
+import kotlinx.coroutines.flow.Flow
+import com.example.model.* // everything
+import com.example.model.User as UserModel;
+
+class UserController {
+    // import not.AnImport
+}
+"#;
+
+        let parsed_file = backend(false).parse_file("A.kt", file_contents);
+
+        assert_eq!(parsed_file.package, Some("com.example.web".to_string()));
+        assert_eq!(
+            module_names(parsed_file),
+            vec![
+                "kotlinx.coroutines.flow.Flow",
+                "com.example.model.*",
+                "com.example.model.User",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let files = [
+            (
+                "src/main/java/com/example/web/UserController.java",
+                "package com.example.web;\n",
+            ),
+            (
+                "src/main/java/com/example/model/User.java",
+                "package com.example.model;\n",
+            ),
+            (
+                "src/main/kotlin/com/example/model/Group.kt",
+                "package com.example.model\n",
+            ),
+            (
+                "src/main/java/com/example/util/Strings.java",
+                "package com.example.util;\n",
+            ),
+            (
+                "billing/src/main/java/com/example/billing/Invoice.java",
+                "package com.example.billing;\n",
+            ),
+        ];
+        let resolve = |packages: bool, from: usize, module_name: &str| {
+            let backend = backend(packages);
+            let node_paths = node_paths(&backend, &files);
+            let node_paths: Vec<&str> = node_paths.iter().map(String::as_str).collect();
+            let nodes = test_nodes(&node_paths, |_| NodeType::Internal);
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(
+            resolve(false, 0, "com.example.model.User"),
+            some("src/main/java/com/example/model/User.java")
+        );
+        assert_eq!(
+            resolve(false, 0, "com.example.model.Group"),
+            some("src/main/kotlin/com/example/model/Group.kt")
+        );
+        assert_eq!(
+            resolve(false, 0, "com.example.util.Strings.join"),
+            some("src/main/java/com/example/util/Strings.java")
+        );
+        assert_eq!(
+            resolve(false, 0, "com.example.billing.Invoice"),
+            some("billing/src/main/java/com/example/billing/Invoice.java")
+        );
+        assert_eq!(resolve(false, 0, "com.example.billing.Missing"), None);
+        assert_eq!(resolve(false, 0, "com.example.model.*"), None);
+        assert_eq!(
+            resolve(false, 0, "java.util.List"),
+            some("external java.util")
+        );
+        assert_eq!(
+            resolve(false, 0, "org.springframework.web.bind.annotation.*"),
+            some("external org.springframework.web.bind.annotation")
+        );
+        assert_eq!(resolve(false, 0, "com.example.util.slugify"), None);
+        assert_eq!(
+            resolve(false, 0, "kotlinx.coroutines.flow.flowOf"),
+            some("external kotlinx.coroutines.flow")
+        );
+
+        assert_eq!(
+            resolve(true, 0, "com.example.model.*"),
+            some("com/example/model")
+        );
+        assert_eq!(
+            resolve(true, 0, "com.example.model.Group"),
+            some("com/example/model")
+        );
+        assert_eq!(
+            resolve(true, 0, "com.example.billing.Invoice"),
+            some("com/example/billing")
+        );
+        assert_eq!(resolve(true, 1, "com.example.model.User"), None);
+        assert_eq!(
+            resolve(true, 0, "com.example.util.slugify"),
+            some("com/example/util")
+        );
+
+        let backend = backend(true);
+        let files = [
+            (
+                "app/src/main/kotlin/com/example/model/Group.kt",
+                "package com.example.model\n",
+            ),
+            (
+                "src/main/kotlin/Extensions.kt",
+                "// Helpers\npackage com.example.util\n\nimport kotlin.math.max\n",
+            ),
+            (
+                "src/main/java/com/example/Legacy.java",
+                "/* Moved */\npackage com.example.model;\n\nclass Legacy {}\n",
+            ),
+            ("src/main/java/Main.java", "class Main {}\n"),
+            ("scripts/Build.java", "class Build {}\n"),
+        ];
+        assert_eq!(
+            node_paths(&backend, &files),
+            vec![
+                "com/example/model",
+                "com/example/util",
+                "com/example/model",
+                ".",
+                "scripts"
+            ]
+        );
+        assert!(matches!(
+            backend.external_node_type("java.util"),
+            NodeType::Stdlib
+        ));
+        assert!(matches!(
+            backend.external_node_type("org.springframework"),
+            NodeType::External
+        ));
+    }
+}
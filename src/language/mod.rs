@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::config::Config;
+use crate::graph::{Node, NodeType};
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::ParsedFile;
+use crate::source::SourceTree;
+
+pub mod cpp;
+pub mod go;
+pub mod jvm;
+pub mod python;
+mod python_stdlib;
+pub mod rust;
+pub mod typescript;
+
+/// Everything the graph builder needs to know about a programming language:
+/// which files to read, how to find their imports, and what those imports
+/// refer to.
+///
+/// Backends are picked by the `language` field of the config.
+pub trait LanguageBackend {
+    /// Extensions of the source files to analyze, without leading dots.
+    fn source_extensions(&self) -> &[String];
+
+    /// Names of directories whose files aren't analyzed, wherever they are,
+    /// like vendored dependencies.
+    fn skipped_directories(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Path of the node a source file belongs to, relative to the project root.
+    /// Each file is its own node unless a backend groups them, e.g. by the
+    /// package the file declares, which `parse_file` returned.
+    fn node_path(&self, path_relative: &str, _package: Option<&str>) -> String {
+        path_relative.to_string()
+    }
+
+    /// The type of the node at `node_path`; all the same unless a backend
+    /// reads other kinds of files along with the code.
+    fn node_type(&self, _node_path: &str) -> NodeType {
+        NodeType::Internal
+    }
+
+    /// Finds the imports in a source file.
+    fn parse_file(&self, path_relative: &str, file_contents: &str) -> ParsedFile;
+
+    /// Works out which module an import from `node` refers to, or returns
+    /// `None` if it doesn't refer to another module.
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport>;
+
+    /// The type of the node made for an external module, so that standard
+    /// library modules can be told apart from third-party packages.
+    fn external_node_type(&self, _module_name: &str) -> NodeType {
+        NodeType::External
+    }
+}
+
+/// The extensions listed in the config without their leading dots, or
+/// `defaults` if it doesn't list any.
+pub fn configured_extensions(config: &Config, defaults: &[&str]) -> Vec<String> {
+    match &config.extensions {
+        Some(extensions) => extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.').to_string())
+            .collect(),
+        None => defaults
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect(),
+    }
+}
+
+/// Names accepted in the `language` field of the config.
+pub const LANGUAGES: [&str; 10] = [
+    "typescript",
+    "javascript",
+    "rust",
+    "python",
+    "go",
+    "java",
+    "kotlin",
+    "c",
+    "cpp",
+    "c++",
+];
+
+/// Makes the backend for the config's language, reading manifests like
+/// `go.mod` or `Cargo.toml` from `source`, so they match the analyzed files.
+pub fn backend_for_config(
+    config: &Config,
+    source: &dyn SourceTree,
+) -> Result<Box<dyn LanguageBackend>, Box<dyn Error>> {
+    match config.language.to_lowercase().as_str() {
+        "typescript" | "javascript" => Ok(Box::new(typescript::TypeScriptBackend::new(config))),
+        "rust" => Ok(Box::new(rust::RustBackend::new(config, source)?)),
+        "python" => Ok(Box::new(python::PythonBackend::new(config))),
+        "go" => Ok(Box::new(go::GoBackend::new(config, source)?)),
+        "java" | "kotlin" => Ok(Box::new(jvm::JvmBackend::new(config))),
+        "c" | "cpp" | "c++" => Ok(Box::new(cpp::CppBackend::new(config, source)?)),
+        language => Err(format!(
+            "Unknown language {:?} in config, expected one of: {}",
+            language,
+            LANGUAGES.join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Makes a node for each path, with the path as its id, for testing how
+/// imports between them resolve.
+#[cfg(test)]
+pub fn test_nodes(paths: &[&str], node_type: impl Fn(&str) -> NodeType) -> Vec<Node> {
+    paths
+        .iter()
+        .map(|path| Node {
+            id: path.to_string(),
+            node_type: node_type(path),
+            label: path.to_string(),
+            path_absolute: format!("/repo/{}", path),
+            path_relative: path.to_string(),
+            metrics: None,
+            size: None,
+        })
+        .collect()
+}
+
+/// Resolves an import from `nodes[from]`, returning the target path, or
+/// `external <module>` for an external module, so tests can compare it.
+#[cfg(test)]
+pub fn resolve_test_import(
+    nodes: &[Node],
+    from: usize,
+    module_name: &str,
+    resolve_import: impl FnOnce(
+        UnresolvedImport,
+        &Node,
+        &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport>,
+) -> Option<String> {
+    let nodes_by_path: HashMap<String, &Node> = nodes
+        .iter()
+        .map(|node| (node.path_relative.clone(), node))
+        .collect();
+    let import = UnresolvedImport {
+        module_name: module_name.to_string(),
+    };
+
+    match resolve_import(import, &nodes[from], &nodes_by_path) {
+        Some(ResolvedImport::InternalImport { target_path, .. }) => Some(target_path),
+        Some(ResolvedImport::ExternalImport { target_module_name }) => {
+            Some(format!("external {}", target_module_name))
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::source::WorkingDirectory;
+
+    fn config(language: &str) -> Config {
+        serde_yaml::from_str(&format!(
+            "title: test\nlanguage: {}\npath: /repo\n",
+            language
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_backend_for_config() {
+        let source = WorkingDirectory::new("/repo");
+
+        let backend = backend_for_config(&config("TypeScript"), &source).unwrap();
+        assert!(backend.source_extensions().contains(&"tsx".to_string()));
+
+        let error = backend_for_config(&config("cobol"), &source).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unknown language \"cobol\" in config, expected one of: typescript, javascript, rust, python, go, java, kotlin, c, cpp, c++"
+        );
+    }
+}
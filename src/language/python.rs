@@ -0,0 +1,308 @@
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tree_sitter::{Node as SyntaxNode, Parser};
+
+use super::python_stdlib::STDLIB_MODULES;
+use super::{configured_extensions, LanguageBackend};
+use crate::config::Config;
+use crate::graph::{Node, NodeType};
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::{find_syntax_errors, node_text, ParsedFile};
+use crate::source::normalize_path;
+
+/// Directories absolute imports are looked up in, for a flat or a `src` layout.
+const DEFAULT_SOURCE_ROOTS: [&str; 2] = [".", "src"];
+
+/// Python, with one node per source file.
+///
+/// Imports are recorded as dotted module names, with the leading dots of
+/// relative imports kept. `from a import b` is recorded as `a.b`, since `b`
+/// may be a submodule; resolution picks the longest prefix that's a module.
+pub struct PythonBackend {
+    extensions: Vec<String>,
+    /// Directories absolute imports start from, relative to the project root
+    source_roots: Vec<PathBuf>,
+    /// The directories that hold modules, at any depth, found from the nodes
+    /// when the first import is resolved
+    module_directories: OnceCell<HashSet<PathBuf>>,
+}
+
+impl PythonBackend {
+    pub fn new(config: &Config) -> Self {
+        let extensions = configured_extensions(config, &["py"]);
+        let source_roots = match &config.source_roots {
+            Some(source_roots) => source_roots.iter().map(String::as_str).collect(),
+            None => DEFAULT_SOURCE_ROOTS.to_vec(),
+        };
+
+        PythonBackend {
+            extensions,
+            source_roots: source_roots
+                .into_iter()
+                .map(|source_root| normalize_path(Path::new(source_root)))
+                .collect(),
+            module_directories: OnceCell::new(),
+        }
+    }
+
+    fn module_directories(&self, nodes_by_path: &HashMap<String, &Node>) -> &HashSet<PathBuf> {
+        self.module_directories.get_or_init(|| {
+            nodes_by_path
+                .keys()
+                .flat_map(|path| Path::new(path).ancestors().skip(1))
+                .map(Path::to_path_buf)
+                .collect()
+        })
+    }
+}
+
+impl LanguageBackend for PythonBackend {
+    fn source_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    fn parse_file(&self, _path_relative: &str, file_contents: &str) -> ParsedFile {
+        let mut parser = Parser::new();
+        parser.set_language(tree_sitter_python::language()).unwrap();
+        let tree = parser.parse(file_contents, None).unwrap();
+
+        let mut imports = vec![];
+        collect_imports(tree.root_node(), file_contents, &mut imports);
+
+        ParsedFile {
+            imports,
+            warnings: find_syntax_errors(&tree, file_contents),
+            package: None,
+        }
+    }
+
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport> {
+        let module_name = import.module_name.trim_start_matches('.');
+        let level = import.module_name.len() - module_name.len();
+        let segments: Vec<&str> = module_name
+            .split('.')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        let bases = if level > 0 {
+            // One dot is the file's own package, each extra dot goes up one more
+            let mut base = Path::new(&node.path_relative).parent()?.to_path_buf();
+            for _ in 1..level {
+                if !base.pop() {
+                    return None;
+                }
+            }
+            vec![base]
+        } else {
+            self.source_roots.clone()
+        };
+
+        for base in bases.iter() {
+            let shortest = if level > 0 { 0 } else { 1 };
+            for length in (shortest..=segments.len()).rev() {
+                let module_path = base.join(segments[..length].join("/"));
+                let candidates = [
+                    module_path.with_extension("py"),
+                    module_path.join("__init__.py"),
+                ];
+                for candidate in candidates {
+                    let candidate = normalize_path(&candidate).to_str().unwrap().to_string();
+                    if let Some(target_node) = nodes_by_path.get(&candidate) {
+                        if target_node.id == node.id {
+                            return None;
+                        }
+                        return Some(ResolvedImport::InternalImport {
+                            target_path: candidate,
+                            target_node_id: target_node.id.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if level > 0 || segments.is_empty() {
+            return None;
+        }
+
+        // A namespace package: a directory of modules without `__init__.py`
+        let module_directories = self.module_directories(nodes_by_path);
+        let is_namespace_package = bases
+            .iter()
+            .any(|base| module_directories.contains(&normalize_path(&base.join(segments[0]))));
+        if is_namespace_package {
+            return None;
+        }
+
+        Some(ResolvedImport::ExternalImport {
+            target_module_name: segments[0].to_string(),
+        })
+    }
+
+    fn external_node_type(&self, module_name: &str) -> NodeType {
+        if STDLIB_MODULES.contains(&module_name) {
+            NodeType::Stdlib
+        } else {
+            NodeType::External
+        }
+    }
+}
+
+fn collect_imports(node: SyntaxNode, file_contents: &str, imports: &mut Vec<UnresolvedImport>) {
+    let mut add = |module_name: String| {
+        if !imports
+            .iter()
+            .any(|import| import.module_name == module_name)
+        {
+            imports.push(UnresolvedImport { module_name });
+        }
+    };
+
+    match node.kind() {
+        "import_statement" => {
+            for name in imported_names(node, file_contents) {
+                add(name);
+            }
+        }
+        "import_from_statement" => {
+            let module_name = node_text(
+                node.child_by_field_name("module_name").unwrap(),
+                file_contents,
+            );
+            let names = imported_names(node, file_contents);
+            if names.is_empty() {
+                // `from a import *`
+                add(module_name);
+            } else {
+                for name in names {
+                    if module_name.ends_with('.') {
+                        add(format!("{}{}", module_name, name));
+                    } else {
+                        add(format!("{}.{}", module_name, name));
+                    }
+                }
+            }
+        }
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_imports(child, file_contents, imports);
+            }
+        }
+    }
+}
+
+/// Returns the dotted names after `import`, without any `as` aliases.
+fn imported_names(node: SyntaxNode, file_contents: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    let names = node
+        .children_by_field_name("name", &mut cursor)
+        .map(|name| match name.kind() {
+            "aliased_import" => name.child_by_field_name("name").unwrap(),
+            _ => name,
+        })
+        .map(|name| node_text(name, file_contents))
+        .collect();
+
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::language::{resolve_test_import, test_nodes};
+
+    fn backend() -> PythonBackend {
+        PythonBackend {
+            extensions: vec!["py".to_string()],
+            source_roots: vec![PathBuf::new(), PathBuf::from("src")],
+            module_directories: OnceCell::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let file_contents = r#"
+import os, os.path as osp
+import numpy as np
+from . import utils
+from .models import User, Group as G
+from ..core.db import *
+from app.services import billing
+
+def handler():
+    import json
+"#;
+
+        let module_names: Vec<String> = backend()
+            .parse_file("src/app/views.py", file_contents)
+            .imports
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect();
+
+        assert_eq!(
+            module_names,
+            vec![
+                "os",
+                "os.path",
+                "numpy",
+                ".utils",
+                ".models.User",
+                ".models.Group",
+                "..core.db",
+                "app.services.billing",
+                "json",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let paths = [
+            "src/app/__init__.py",
+            "src/app/views.py",
+            "src/app/models.py",
+            "src/app/services/billing.py",
+            "src/core/db/__init__.py",
+            "src/plugins/stripe.py",
+            "manage.py",
+        ];
+        let nodes = test_nodes(&paths, |_| NodeType::Internal);
+
+        let backend = backend();
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(resolve(1, ".models.User"), some("src/app/models.py"));
+        assert_eq!(resolve(1, ".utils"), some("src/app/__init__.py"));
+        assert_eq!(resolve(1, "..core.db"), some("src/core/db/__init__.py"));
+        assert_eq!(
+            resolve(1, "app.services.billing"),
+            some("src/app/services/billing.py")
+        );
+        assert_eq!(resolve(1, "app.services"), some("src/app/__init__.py"));
+        assert_eq!(resolve(6, "app.views.index"), some("src/app/views.py"));
+        assert_eq!(resolve(1, "plugins.stripe"), some("src/plugins/stripe.py"));
+        assert_eq!(resolve(1, "plugins"), None);
+        assert_eq!(resolve(1, "numpy.linalg"), some("external numpy"));
+        assert_eq!(resolve(1, "os.path"), some("external os"));
+        assert_eq!(resolve(1, "...outside"), None);
+
+        assert!(matches!(backend.external_node_type("os"), NodeType::Stdlib));
+        assert!(matches!(
+            backend.external_node_type("numpy"),
+            NodeType::External
+        ));
+    }
+}
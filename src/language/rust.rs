@@ -0,0 +1,618 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+use tree_sitter::{Node as SyntaxNode, Parser};
+
+use super::{configured_extensions, LanguageBackend};
+use crate::config::Config;
+use crate::graph::{Node, NodeType};
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::{find_syntax_errors, node_text, ParsedFile};
+use crate::source::{normalize_path, SourceTree};
+
+/// Crates that come with the compiler rather than from Cargo.toml.
+const BUILTIN_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+
+/// Tables in Cargo.toml that list dependencies.
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Rust, with one node per source file.
+///
+/// The parser records paths rather than files:
+/// - `mod foo;` becomes `self::foo`, or `./<path>` when it has a `#[path]` attribute
+/// - `use` declarations and qualified paths in code, like `crate::a::b` or
+///   `serde::Serialize`, are kept as written, once they're made relative to
+///   the file's module rather than any inline module inside it
+///
+/// Resolution then maps module paths to files using the usual layout, where
+/// `crate::a::b` lives in `a/b.rs` or `a/b/mod.rs` next to `lib.rs` or `main.rs`.
+pub struct RustBackend {
+    extensions: Vec<String>,
+    /// Names of the dependencies in Cargo.toml, keyed by the name used in code
+    crate_names: HashMap<String, String>,
+}
+
+impl RustBackend {
+    pub fn new(config: &Config, source: &dyn SourceTree) -> Result<Self, Box<dyn Error>> {
+        let extensions = configured_extensions(config, &["rs"]);
+
+        Ok(RustBackend {
+            extensions,
+            crate_names: read_dependency_names(source, Path::new(""))?,
+        })
+    }
+
+    /// Whether a path starting with this name refers to another crate.
+    fn is_crate_name(&self, name: &str) -> bool {
+        self.crate_names.contains_key(name) || BUILTIN_CRATES.contains(&name)
+    }
+}
+
+impl LanguageBackend for RustBackend {
+    fn source_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    fn parse_file(&self, _path_relative: &str, file_contents: &str) -> ParsedFile {
+        let mut parser = Parser::new();
+        parser.set_language(tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(file_contents, None).unwrap();
+
+        let mut visitor = PathVisitor {
+            backend: self,
+            file_contents,
+            local_modules: top_level_modules(tree.root_node(), file_contents),
+            seen: HashSet::new(),
+            imports: vec![],
+        };
+        visitor.visit(tree.root_node(), 0);
+
+        ParsedFile {
+            imports: visitor.imports,
+            warnings: find_syntax_errors(&tree, file_contents),
+            package: None,
+        }
+    }
+
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport> {
+        if let Some(module_file) = import.module_name.strip_prefix("./") {
+            let directory = Path::new(&node.path_relative).parent()?;
+            let target_path = normalize_path(&directory.join(module_file))
+                .to_str()
+                .unwrap()
+                .to_string();
+            return internal_import(node, nodes_by_path, target_path);
+        }
+
+        let location = CrateLocation::find(&node.path_relative, nodes_by_path);
+        let segments: Vec<&str> = import.module_name.split("::").collect();
+
+        let module_path: Vec<String> = match segments[0] {
+            "crate" => to_strings(&segments[1..]),
+            "self" => [location.module_path.clone(), to_strings(&segments[1..])].concat(),
+            "super" => {
+                let supers = segments.iter().take_while(|segment| **segment == "super");
+                let mut module_path = location.module_path.clone();
+                for _ in supers {
+                    module_path.pop()?;
+                }
+                let rest = segments.iter().skip_while(|segment| **segment == "super");
+                [
+                    module_path,
+                    rest.map(|segment| segment.to_string()).collect(),
+                ]
+                .concat()
+            }
+            name if self.is_crate_name(name) => {
+                let target_module_name = self
+                    .crate_names
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| name.to_string());
+                return Some(ResolvedImport::ExternalImport { target_module_name });
+            }
+            // A module declared in this file, used without `self::`
+            _ => [location.module_path.clone(), to_strings(&segments)].concat(),
+        };
+
+        // The path may go on to name an item inside the module, so the
+        // longest prefix that's a file wins
+        for length in (0..=module_path.len()).rev() {
+            let prefix = &module_path[..length];
+            for candidate in location.module_file_candidates(prefix) {
+                if nodes_by_path.contains_key(&candidate) {
+                    return internal_import(node, nodes_by_path, candidate);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn external_node_type(&self, module_name: &str) -> NodeType {
+        if BUILTIN_CRATES.contains(&module_name) {
+            NodeType::Stdlib
+        } else {
+            NodeType::External
+        }
+    }
+}
+
+fn internal_import(
+    node: &Node,
+    nodes_by_path: &HashMap<String, &Node>,
+    target_path: String,
+) -> Option<ResolvedImport> {
+    let target_node = nodes_by_path.get(&target_path)?;
+    if target_node.id == node.id {
+        return None;
+    }
+
+    Some(ResolvedImport::InternalImport {
+        target_path,
+        target_node_id: target_node.id.clone(),
+    })
+}
+
+fn to_strings(segments: &[&str]) -> Vec<String> {
+    segments.iter().map(|segment| segment.to_string()).collect()
+}
+
+/// Where a file sits in its crate's module tree.
+struct CrateLocation {
+    /// Directory that top-level modules of the crate live in
+    crate_directory: PathBuf,
+    /// The crate root, e.g. `src/lib.rs`
+    root_file: String,
+    /// Path of the file's module, e.g. `["a", "b"]` for `crate::a::b`
+    module_path: Vec<String>,
+}
+
+impl CrateLocation {
+    fn find(path_relative: &str, nodes_by_path: &HashMap<String, &Node>) -> CrateLocation {
+        let path = Path::new(path_relative);
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let file_name = path.file_name().and_then(|name| name.to_str());
+        let directory_name = directory.file_name().and_then(|name| name.to_str());
+
+        let is_crate_root = matches!(file_name, Some("lib.rs" | "main.rs" | "build.rs"))
+            || matches!(
+                directory_name,
+                Some("bin" | "tests" | "examples" | "benches")
+            );
+        if !is_crate_root {
+            for ancestor in directory.ancestors() {
+                for root_name in ["lib.rs", "main.rs"] {
+                    let root_file = ancestor.join(root_name).to_str().unwrap().to_string();
+                    if !nodes_by_path.contains_key(&root_file) {
+                        continue;
+                    }
+
+                    let mut module_path: Vec<String> = path
+                        .strip_prefix(ancestor)
+                        .unwrap()
+                        .with_extension("")
+                        .iter()
+                        .map(|segment| segment.to_str().unwrap().to_string())
+                        .collect();
+                    if module_path.last().is_some_and(|segment| segment == "mod") {
+                        module_path.pop();
+                    }
+
+                    return CrateLocation {
+                        crate_directory: ancestor.to_path_buf(),
+                        root_file,
+                        module_path,
+                    };
+                }
+            }
+        }
+
+        CrateLocation {
+            crate_directory: directory.to_path_buf(),
+            root_file: path_relative.to_string(),
+            module_path: vec![],
+        }
+    }
+
+    /// Files that could hold the module at `module_path` in this crate.
+    fn module_file_candidates(&self, module_path: &[String]) -> Vec<String> {
+        if module_path.is_empty() {
+            return vec![self.root_file.clone()];
+        }
+
+        let module_directory = self.crate_directory.join(module_path.join("/"));
+        vec![
+            module_directory.with_extension("rs"),
+            module_directory.join("mod.rs"),
+        ]
+        .into_iter()
+        .map(|candidate| candidate.to_str().unwrap().to_string())
+        .collect()
+    }
+}
+
+/// Collects the module paths a file refers to, skipping duplicates.
+struct PathVisitor<'a> {
+    backend: &'a RustBackend,
+    file_contents: &'a str,
+    /// Modules declared with `mod foo;` at the top of the file
+    local_modules: HashSet<String>,
+    seen: HashSet<String>,
+    imports: Vec<UnresolvedImport>,
+}
+
+impl PathVisitor<'_> {
+    /// Visits a syntax node inside `inline_depth` inline `mod { ... }` blocks.
+    fn visit(&mut self, node: SyntaxNode, inline_depth: usize) {
+        match node.kind() {
+            "mod_item" => match node.child_by_field_name("body") {
+                Some(body) => self.visit(body, inline_depth + 1),
+                None if inline_depth == 0 => {
+                    let name = node_text(
+                        node.child_by_field_name("name").unwrap(),
+                        self.file_contents,
+                    );
+                    match path_attribute(node, self.file_contents) {
+                        Some(path) => self.add(format!("./{}", path)),
+                        None => self.add(format!("self::{}", name)),
+                    }
+                }
+                None => {}
+            },
+            "use_declaration" => {
+                if let Some(argument) = node.child_by_field_name("argument") {
+                    for path in expand_use_tree(&node_text(argument, self.file_contents)) {
+                        self.add_path(&path, inline_depth);
+                    }
+                }
+            }
+            "extern_crate_declaration" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.add_path(&node_text(name, self.file_contents), inline_depth);
+                }
+            }
+            "scoped_identifier" | "scoped_type_identifier" => {
+                self.add_path(&node_text(node, self.file_contents), inline_depth);
+            }
+            _ => {
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    self.visit(child, inline_depth);
+                }
+            }
+        }
+    }
+
+    /// Keeps a path if it could refer to another file or crate, rewriting
+    /// `self` and `super` to be relative to the file's module.
+    fn add_path(&mut self, path: &str, inline_depth: usize) {
+        // Drop generic arguments, e.g. `HashMap::<K, V>::new`
+        let path: String = path.split('<').next().unwrap().split_whitespace().collect();
+        let path = path.trim_end_matches("::");
+        let segments: Vec<&str> = path.split("::").collect();
+        if segments.len() < 2 && !self.backend.is_crate_name(segments[0]) {
+            return;
+        }
+
+        match segments[0] {
+            "crate" => self.add(path.to_string()),
+            "self" | "super" => {
+                let supers = segments
+                    .iter()
+                    .take_while(|segment| **segment == "super")
+                    .count();
+                let rest = &segments[supers.max(1)..];
+                if segments[0] == "self" && inline_depth > 0 || supers > 0 && supers < inline_depth
+                {
+                    // Somewhere inside this file
+                    return;
+                }
+                let prefix = if supers > inline_depth {
+                    vec!["super"; supers - inline_depth]
+                } else if rest.is_empty() {
+                    // The file's own module
+                    return;
+                } else {
+                    vec!["self"]
+                };
+                self.add([prefix, rest.to_vec()].concat().join("::"));
+            }
+            name if self.backend.is_crate_name(name) => self.add(path.to_string()),
+            name if inline_depth == 0 && self.local_modules.contains(name) => {
+                self.add(format!("self::{}", path))
+            }
+            _ => {}
+        }
+    }
+
+    fn add(&mut self, module_name: String) {
+        if self.seen.insert(module_name.clone()) {
+            self.imports.push(UnresolvedImport { module_name });
+        }
+    }
+}
+
+/// Names of the modules declared with `mod foo;` directly in the file.
+fn top_level_modules(root: SyntaxNode, file_contents: &str) -> HashSet<String> {
+    let mut cursor = root.walk();
+    let modules = root
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "mod_item")
+        .filter_map(|child| child.child_by_field_name("name"))
+        .map(|name| node_text(name, file_contents))
+        .collect();
+
+    modules
+}
+
+/// Reads `#[path = "..."]` from the attributes right before a `mod` item.
+fn path_attribute(mod_item: SyntaxNode, file_contents: &str) -> Option<String> {
+    let mut sibling = mod_item.prev_named_sibling();
+    while let Some(attribute_item) = sibling.filter(|node| node.kind() == "attribute_item") {
+        let text: String = node_text(attribute_item, file_contents)
+            .split_whitespace()
+            .collect();
+        if let Some(path) = text
+            .strip_prefix("#[path=\"")
+            .and_then(|rest| rest.strip_suffix("\"]"))
+        {
+            return Some(path.to_string());
+        }
+        sibling = attribute_item.prev_named_sibling();
+    }
+
+    None
+}
+
+/// Expands the argument of a `use` declaration into the paths it imports,
+/// e.g. `a::{b, c::{self, d as e}, f::*}` into `a::b`, `a::c`, `a::c::d` and `a::f`.
+fn expand_use_tree(use_tree: &str) -> Vec<String> {
+    let use_tree = use_tree.trim();
+
+    match use_tree.find('{') {
+        Some(open) if use_tree.ends_with('}') => {
+            let prefix = use_tree[..open].trim().trim_end_matches("::");
+            let inner = &use_tree[open + 1..use_tree.len() - 1];
+            split_top_level(inner)
+                .into_iter()
+                .flat_map(expand_use_tree)
+                .map(|item| match (prefix, item.as_str()) {
+                    ("", _) => item,
+                    (_, "self") => prefix.to_string(),
+                    _ => format!("{}::{}", prefix, item),
+                })
+                .collect()
+        }
+        _ => {
+            let path = use_tree.split(" as ").next().unwrap().trim();
+            let path = path.trim_end_matches('*').trim_end_matches("::");
+            if path.is_empty() {
+                vec![]
+            } else {
+                vec![path.to_string()]
+            }
+        }
+    }
+}
+
+/// Splits a `use` list on the commas that aren't inside nested braces.
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, character) in list.char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&list[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&list[start..]);
+
+    items
+        .into_iter()
+        .filter(|item| !item.trim().is_empty())
+        .collect()
+}
+
+/// Reads the dependencies of the package in `directory`, relative to the
+/// project root, or of every member if it's a workspace, keyed by the name
+/// code refers to them with.
+fn read_dependency_names(
+    source: &dyn SourceTree,
+    directory: &Path,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut crate_names = HashMap::new();
+
+    let manifest_path = directory.join("Cargo.toml");
+    let manifest: toml::Table = match source.read_file(manifest_path.to_str().unwrap()) {
+        Ok(contents) => contents.parse()?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(crate_names),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut tables = vec![&manifest];
+    if let Some(targets) = manifest
+        .get("target")
+        .and_then(|targets| targets.as_table())
+    {
+        tables.extend(targets.values().filter_map(|target| target.as_table()));
+    }
+    for table in tables {
+        for table_name in DEPENDENCY_TABLES {
+            let Some(dependencies) = table.get(table_name).and_then(|value| value.as_table())
+            else {
+                continue;
+            };
+            for name in dependencies.keys() {
+                crate_names.insert(name.replace('-', "_"), name.clone());
+            }
+        }
+    }
+
+    let members = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array());
+    for member in members
+        .into_iter()
+        .flatten()
+        .filter_map(|member| member.as_str())
+    {
+        let pattern = normalize_path(&directory.join(member).join("Cargo.toml"));
+        for member_manifest_path in source.find_files(pattern.to_str().unwrap())? {
+            let member_directory = Path::new(&member_manifest_path).parent().unwrap();
+            crate_names.extend(read_dependency_names(source, member_directory)?);
+        }
+    }
+
+    Ok(crate_names)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::NodeType;
+    use crate::language::{resolve_test_import, test_nodes};
+
+    fn backend(crate_names: &[&str]) -> RustBackend {
+        RustBackend {
+            extensions: vec!["rs".to_string()],
+            crate_names: crate_names
+                .iter()
+                .map(|name| (name.replace('-', "_"), name.to_string()))
+                .collect(),
+        }
+    }
+
+    fn module_names(backend: &RustBackend, file_contents: &str) -> Vec<String> {
+        backend
+            .parse_file("src/lib.rs", file_contents)
+            .imports
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_use_tree() {
+        assert_eq!(
+            expand_use_tree("crate::graph::Node"),
+            vec!["crate::graph::Node"]
+        );
+        assert_eq!(
+            expand_use_tree("std::collections::{HashMap, hash_map::{self, Entry as E}}"),
+            vec![
+                "std::collections::HashMap",
+                "std::collections::hash_map",
+                "std::collections::hash_map::Entry"
+            ]
+        );
+        assert_eq!(expand_use_tree("super::*"), vec!["super"]);
+        assert_eq!(expand_use_tree("{a::b, c}"), vec!["a::b", "c"]);
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let backend = backend(&["serde", "tree-sitter"]);
+        let file_contents = r#"
+mod config;
+#[path = "generated/schema.rs"]
+mod schema;
+
+use serde::{Deserialize, Serialize};
+use config::Config;
+use crate::graph::{Edge, Node};
+use self::config::read_config_file;
+use std::collections::HashMap;
+use Ordering::Less;
+
+fn main() {
+    let language = tree_sitter::Language::new();
+    let value = crate::progress::is_quiet();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::other;
+    use crate::graph::Edge;
+}
+"#;
+
+        assert_eq!(
+            module_names(&backend, file_contents),
+            vec![
+                "self::config",
+                "./generated/schema.rs",
+                "serde::Deserialize",
+                "serde::Serialize",
+                "self::config::Config",
+                "crate::graph::Edge",
+                "crate::graph::Node",
+                "self::config::read_config_file",
+                "std::collections::HashMap",
+                "tree_sitter::Language::new",
+                "crate::progress::is_quiet",
+                "super::other",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let backend = backend(&["tree-sitter"]);
+        let paths = [
+            "src/main.rs",
+            "src/graph.rs",
+            "src/export/mod.rs",
+            "src/export/dot.rs",
+            "src/generated/schema.rs",
+        ];
+        let nodes = test_nodes(&paths, |_| NodeType::Internal);
+
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(resolve(0, "self::export"), some("src/export/mod.rs"));
+        assert_eq!(
+            resolve(0, "./generated/schema.rs"),
+            some("src/generated/schema.rs")
+        );
+        assert_eq!(resolve(0, "crate::graph::Node"), some("src/graph.rs"));
+        assert_eq!(resolve(2, "self::dot"), some("src/export/dot.rs"));
+        assert_eq!(resolve(3, "super::escape_xml"), some("src/export/mod.rs"));
+        assert_eq!(
+            resolve(3, "super::super::graph::Graph"),
+            some("src/graph.rs")
+        );
+        assert_eq!(resolve(3, "crate::Cli"), some("src/main.rs"));
+        assert_eq!(
+            resolve(0, "tree_sitter::Parser"),
+            some("external tree-sitter")
+        );
+        assert_eq!(resolve(0, "std::fs"), some("external std"));
+        assert!(matches!(
+            backend.external_node_type("std"),
+            NodeType::Stdlib
+        ));
+        assert_eq!(resolve(1, "crate::graph::Edge"), None);
+        assert_eq!(resolve(1, "crate::missing::Thing"), some("src/main.rs"));
+    }
+}
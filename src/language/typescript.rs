@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use super::{configured_extensions, LanguageBackend};
+use crate::config::{Config, ModuleResolutionItem};
+use crate::graph::{Node, NodeType};
+use crate::graphql::{
+    is_graphql_document, parse_graphql_imports, resolve_graphql_import, GRAPHQL_EXTENSIONS,
+};
+use crate::imports::{resolve_import, ResolvedImport, UnresolvedImport};
+use crate::parse::{file_extension, parse_source_file, ParsedFile};
+use crate::stylesheet::{
+    is_stylesheet, parse_stylesheet_imports, resolve_stylesheet_import, STYLESHEET_EXTENSIONS,
+};
+
+/// Extensions of code files, in the order they're tried when resolving an
+/// import without one.
+pub const DEFAULT_EXTENSIONS: [&str; 10] = [
+    "tsx", "ts", "jsx", "js", "mts", "cts", "mjs", "cjs", "vue", "svelte",
+];
+
+/// Extensions of the files that code can import without them being code,
+/// e.g. with a bundler: images, fonts, media and data.
+pub const ASSET_EXTENSIONS: [&str; 17] = [
+    "png", "jpg", "jpeg", "gif", "webp", "avif", "ico", "svg", "json", "woff", "woff2", "ttf",
+    "otf", "eot", "mp4", "webm", "mp3",
+];
+
+/// JavaScript and TypeScript, including Vue and Svelte components, and the
+/// stylesheets, GraphQL documents and assets they import.
+pub struct TypeScriptBackend {
+    extensions: Vec<String>,
+    /// Extensions tried when a code import leaves one out, i.e. without the
+    /// stylesheet, GraphQL and asset ones
+    import_extensions: Vec<String>,
+    module_resolution: Option<Vec<ModuleResolutionItem>>,
+}
+
+impl TypeScriptBackend {
+    pub fn new(config: &Config) -> Self {
+        let import_extensions: Vec<String> = configured_extensions(config, &DEFAULT_EXTENSIONS)
+            .into_iter()
+            .filter(|extension| !is_non_code_extension(extension))
+            .collect();
+        // The config only picks the code files: stylesheets, GraphQL documents
+        // and assets are read whenever code imports them
+        let extensions = import_extensions
+            .iter()
+            .cloned()
+            .chain(
+                STYLESHEET_EXTENSIONS
+                    .iter()
+                    .chain(GRAPHQL_EXTENSIONS.iter())
+                    .chain(ASSET_EXTENSIONS.iter())
+                    .map(|extension| extension.to_string()),
+            )
+            .collect();
+
+        TypeScriptBackend {
+            extensions,
+            import_extensions,
+            module_resolution: config.module_resolution.clone(),
+        }
+    }
+}
+
+fn is_non_code_extension(extension: &str) -> bool {
+    STYLESHEET_EXTENSIONS.contains(&extension)
+        || GRAPHQL_EXTENSIONS.contains(&extension)
+        || ASSET_EXTENSIONS.contains(&extension)
+}
+
+impl LanguageBackend for TypeScriptBackend {
+    fn source_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    fn node_type(&self, node_path: &str) -> NodeType {
+        if is_stylesheet(node_path) {
+            NodeType::Stylesheet
+        } else if is_asset(node_path) {
+            NodeType::Asset
+        } else {
+            NodeType::Internal
+        }
+    }
+
+    fn parse_file(&self, path_relative: &str, file_contents: &str) -> ParsedFile {
+        if is_stylesheet(path_relative) {
+            return ParsedFile {
+                imports: parse_stylesheet_imports(file_contents),
+                warnings: vec![],
+                package: None,
+            };
+        }
+        if is_graphql_document(path_relative) {
+            return ParsedFile {
+                imports: parse_graphql_imports(file_contents),
+                warnings: vec![],
+                package: None,
+            };
+        }
+
+        parse_source_file(path_relative, file_contents)
+    }
+
+    fn resolve_import(
+        &self,
+        import: UnresolvedImport,
+        node: &Node,
+        nodes_by_path: &HashMap<String, &Node>,
+    ) -> Option<ResolvedImport> {
+        if is_stylesheet(&node.path_relative) {
+            return resolve_stylesheet_import(import, node, nodes_by_path, &self.module_resolution);
+        }
+        if is_graphql_document(&node.path_relative) {
+            return resolve_graphql_import(import, node, nodes_by_path, &self.module_resolution);
+        }
+
+        Some(resolve_import(
+            import,
+            node,
+            nodes_by_path,
+            &self.module_resolution,
+            &self.import_extensions,
+        ))
+    }
+}
+
+fn is_asset(path_relative: &str) -> bool {
+    file_extension(path_relative)
+        .is_some_and(|extension| ASSET_EXTENSIONS.contains(&extension.as_str()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::language::{resolve_test_import, test_nodes};
+
+    fn backend() -> TypeScriptBackend {
+        let config: Config =
+            serde_yaml::from_str("title: test\nlanguage: typescript\npath: /repo\n").unwrap();
+        TypeScriptBackend::new(&config)
+    }
+
+    #[test]
+    fn test_stylesheets() {
+        let backend = backend();
+
+        let paths = ["src/App.tsx", "src/Button.scss", "src/_theme.scss"];
+        let nodes = test_nodes(&paths, |path| backend.node_type(path));
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        assert!(matches!(nodes[0].node_type, NodeType::Internal));
+        assert!(matches!(nodes[1].node_type, NodeType::Stylesheet));
+        assert_eq!(
+            resolve(0, "./Button.scss"),
+            Some("src/Button.scss".to_string())
+        );
+        // Code imports without an extension don't pick up stylesheets
+        assert_eq!(
+            resolve(0, "./Button"),
+            Some("external ./Button".to_string())
+        );
+
+        let imports = backend
+            .parse_file("src/Button.scss", "@use \"theme\";\n")
+            .imports;
+        assert_eq!(imports[0].module_name, "theme");
+        assert_eq!(resolve(1, "theme"), Some("src/_theme.scss".to_string()));
+    }
+
+    #[test]
+    fn test_configured_extensions_keep_non_code_files() {
+        let config: Config = serde_yaml::from_str(
+            "title: test\nlanguage: typescript\npath: /repo\nextensions: [ts, tsx, scss]\n",
+        )
+        .unwrap();
+        let backend = TypeScriptBackend::new(&config);
+
+        assert_eq!(backend.import_extensions, vec!["ts", "tsx"]);
+        let extensions = backend.source_extensions();
+        assert_eq!(&extensions[..2], ["ts", "tsx"]);
+        for extension in ["scss", "css", "graphql", "svg", "json"] {
+            assert!(extensions.iter().any(|configured| configured == extension));
+        }
+        assert!(!extensions.iter().any(|configured| configured == "js"));
+    }
+
+    #[test]
+    fn test_upper_case_extensions() {
+        let backend = backend();
+        let module_names = |path: &str, file_contents: &str| -> Vec<String> {
+            let parsed_file = backend.parse_file(path, file_contents);
+            assert!(parsed_file.warnings.is_empty());
+            parsed_file
+                .imports
+                .into_iter()
+                .map(|import| import.module_name)
+                .collect()
+        };
+
+        assert!(matches!(
+            backend.node_type("src/Theme.SCSS"),
+            NodeType::Stylesheet
+        ));
+        assert_eq!(
+            module_names("src/Theme.SCSS", "@use \"colors\";\n"),
+            vec!["colors"]
+        );
+        assert_eq!(
+            module_names(
+                "src/App.Vue",
+                "<template><div /></template>\n<script>\nimport Button from \"./Button.vue\";\n</script>\n"
+            ),
+            vec!["./Button.vue"]
+        );
+        assert_eq!(
+            module_names("src/util.TS", "import type { A } from \"./a\";\n"),
+            vec!["./a"]
+        );
+    }
+
+    #[test]
+    fn test_graphql_documents() {
+        let backend = backend();
+
+        let paths = [
+            "src/UserList.tsx",
+            "src/Users.graphql",
+            "src/UserFields.graphql",
+        ];
+        let nodes = test_nodes(&paths, |path| backend.node_type(path));
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                backend.resolve_import(import, node, nodes_by_path)
+            })
+        };
+
+        assert!(matches!(nodes[1].node_type, NodeType::Internal));
+        assert_eq!(
+            resolve(0, "./Users.graphql"),
+            Some("src/Users.graphql".to_string())
+        );
+        // Code imports without an extension don't pick up GraphQL documents
+        assert_eq!(resolve(0, "./Users"), Some("external ./Users".to_string()));
+
+        let imports = backend
+            .parse_file("src/Users.graphql", "#import \"./UserFields.graphql\"\n")
+            .imports;
+        assert_eq!(imports[0].module_name, "./UserFields.graphql");
+        assert_eq!(
+            resolve(1, "./UserFields.graphql"),
+            Some("src/UserFields.graphql".to_string())
+        );
+    }
+}
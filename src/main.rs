@@ -1,18 +1,45 @@
 use clap::{Parser, Subcommand};
-use std::fs;
+use std::fmt::Display;
 
+mod aggregate;
+mod cargo;
 mod config;
+mod diff;
+mod export;
+mod git;
 mod graph;
+mod graphql;
+mod history;
 mod imports;
+mod language;
+mod metrics;
 mod parse;
+mod progress;
+mod sfc;
+mod source;
+mod stylesheet;
 
-use crate::graph::generate_graph;
+use crate::aggregate::{aggregate_graph, Grouping};
+use crate::cargo::generate_crate_graph;
+use crate::config::{read_config_file, Config};
+use crate::diff::{diff_graphs, render_diff, DiffFormat};
+use crate::export::{render_graph, write_output, ExportOptions, OutputFormat};
+use crate::git::generate_graph_at_revision;
+use crate::graph::{generate_graph, graph_json_schema, read_graph_file, scope_graph, Graph};
+use crate::history::{generate_history, render_history, HistoryFormat};
+use crate::metrics::{find_cycles, top_hubs, Adjacency};
+use crate::parse::ParseCache;
+use crate::progress::{progress, set_quiet};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Don't print progress messages
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -22,6 +49,130 @@ enum Commands {
         /// Location of the config file
         #[arg(short, long)]
         config: String,
+
+        /// Collapse modules into the groups listed in the config file
+        #[arg(long)]
+        collapse: bool,
+
+        /// Collapse modules into directories, keeping this many path components
+        #[arg(long)]
+        collapse_depth: Option<usize>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+
+        /// Cluster internal modules by directory, for formats that support it
+        #[arg(long)]
+        cluster: bool,
+
+        /// Only include modules reachable from this module (relative path or package name)
+        #[arg(long)]
+        root: Option<String>,
+
+        /// Maximum number of imports to follow from the root module
+        #[arg(long, requires = "root")]
+        depth: Option<usize>,
+
+        /// Where to write the graph, or `-` for stdout [default: graph.<format> in the current directory]
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Write JSON without indentation and line breaks
+        #[arg(long)]
+        compact: bool,
+
+        /// Compress the output with gzip
+        #[arg(long)]
+        gzip: bool,
+
+        /// Analyze the project as it was at this git revision, instead of the working directory
+        #[arg(long)]
+        rev: Option<String>,
+
+        /// Graph the crates of the Cargo workspace at the project path, instead of its modules
+        #[arg(long, conflicts_with = "rev")]
+        crates: bool,
+
+        /// Draw one node per package instead of per file, like `packages: true` in the config (Java and Kotlin)
+        #[arg(long, conflicts_with = "crates")]
+        packages: bool,
+    },
+    /// Print coupling metrics for the modules in the graph
+    Stats {
+        /// Location of the config file
+        #[arg(short, long)]
+        config: String,
+
+        /// Number of hub modules to list
+        #[arg(short, long, default_value_t = 10)]
+        top: usize,
+
+        /// Measure the crates of the Cargo workspace at the project path, instead of its modules
+        #[arg(long)]
+        crates: bool,
+    },
+    /// Compare two graphs and report the dependencies that changed
+    Diff {
+        /// Graph file to compare from
+        #[arg(required_unless_present = "base")]
+        old: Option<String>,
+
+        /// Graph file to compare to
+        #[arg(required_unless_present = "base")]
+        new: Option<String>,
+
+        /// Location of the config file, to generate the graphs from git revisions instead
+        #[arg(short, long, requires = "base")]
+        config: Option<String>,
+
+        /// Git revision to compare from
+        #[arg(long, requires = "config", conflicts_with_all = ["old", "new"])]
+        base: Option<String>,
+
+        /// Git revision to compare to
+        #[arg(long, requires = "base", default_value = "HEAD")]
+        head: String,
+
+        /// Report format
+        #[arg(short, long, value_enum, default_value_t = DiffFormat::Text)]
+        format: DiffFormat,
+
+        /// Where to write the report, or `-` for stdout
+        #[arg(short, long, default_value = "-")]
+        output: String,
+    },
+    /// Summarize the graph at commits over time, to track how dependencies change
+    History {
+        /// Location of the config file
+        #[arg(short, long)]
+        config: String,
+
+        /// Git revision to start after [default: the first commit]
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Git revision to end at
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+
+        /// Minimum number of days between analyzed commits
+        #[arg(long, default_value_t = 7)]
+        interval: u32,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = HistoryFormat::Csv)]
+        format: HistoryFormat,
+
+        /// Where to write the time series, or `-` for stdout
+        #[arg(short, long, default_value = "-")]
+        output: String,
+    },
+    /// Print the JSON Schema for the graph.json format
+    Schema {
+        /// Where to write the schema, or `-` for stdout
+        #[arg(short, long, default_value = "-")]
+        output: String,
     },
     /// Generate and serve files
     Serve {
@@ -33,18 +184,166 @@ enum Commands {
 
 fn main() {
     let cli = Cli::parse();
+    set_quiet(cli.quiet);
 
     match cli.command {
-        Some(Commands::Generate { config }) => {
-            let graph = generate_graph(&config);
+        Some(Commands::Generate {
+            config,
+            collapse,
+            collapse_depth,
+            format,
+            cluster,
+            root,
+            depth,
+            output,
+            compact,
+            gzip,
+            rev,
+            crates,
+            packages,
+        }) => {
+            if output.as_deref() == Some("-") {
+                // The graph goes to stdout, so keep it clean
+                set_quiet(true);
+            }
+
+            let mut config = load_config(&config);
+            config.packages |= packages;
+            let mut graph = exit_on_error(match rev {
+                _ if crates => generate_crate_graph(&config),
+                Some(rev) => generate_graph_at_revision(&config, &rev, &mut ParseCache::default()),
+                None => generate_graph(&config),
+            });
+
+            if let Some(root) = root {
+                graph = match scope_graph(&graph, &root, depth) {
+                    Some(scoped_graph) => scoped_graph,
+                    None => {
+                        eprintln!("Root module not found in graph: {}", root);
+                        std::process::exit(1);
+                    }
+                };
+                progress!("Scoped graph to {} nodes.", graph.nodes.len());
+            }
+
+            if collapse || collapse_depth.is_some() {
+                let grouping = match Grouping::new(
+                    collapse_depth,
+                    config.groups.as_deref().unwrap_or_default(),
+                ) {
+                    Ok(grouping) => grouping,
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        std::process::exit(1);
+                    }
+                };
+                graph = aggregate_graph(&graph, &grouping);
+                progress!("Collapsed graph into {} nodes.", graph.nodes.len());
+            }
+
+            progress!("Saving graph as {:?}...", format);
+            let options = ExportOptions { cluster, compact };
+            let contents = render_graph(&graph, format, &options);
+            let output_path = match output {
+                Some(output) => output,
+                None if gzip => format!("{}.gz", format.default_file_name()),
+                None => format.default_file_name().to_string(),
+            };
+            save_output(&output_path, contents.as_bytes(), gzip);
+
+            progress!("Done!");
+        }
+        Some(Commands::Stats {
+            config,
+            top,
+            crates,
+        }) => {
+            let config = load_config(&config);
+            let graph = exit_on_error(if crates {
+                generate_crate_graph(&config)
+            } else {
+                generate_graph(&config)
+            });
+
+            println!();
+            println!("Modules: {}", graph.nodes.len());
+            println!("Edges: {}", graph.edges.len());
+            println!(
+                "Import cycles: {}",
+                find_cycles(&Adjacency::new(&graph)).len()
+            );
+            println!();
+            println!("Top {} hubs:", top);
+            println!(
+                "  {:>6} {:>7} {:>11} {:>10} {:>5}  module",
+                "fan-in", "fan-out", "instability", "transitive", "depth"
+            );
+            for (node, metrics) in top_hubs(&graph, top) {
+                let name = if node.path_relative.is_empty() {
+                    &node.label
+                } else {
+                    &node.path_relative
+                };
+                println!(
+                    "  {:>6} {:>7} {:>11.2} {:>10} {:>5}  {}",
+                    metrics.afferent_coupling,
+                    metrics.efferent_coupling,
+                    metrics.instability,
+                    metrics.transitive_dependencies,
+                    metrics.depth,
+                    name
+                );
+            }
+        }
+        Some(Commands::Diff {
+            old,
+            new,
+            config,
+            base,
+            head,
+            format,
+            output,
+        }) => {
+            if output == "-" {
+                set_quiet(true);
+            }
+
+            let (old_graph, new_graph) = match (config, base) {
+                (Some(config), Some(base)) => {
+                    let config = load_config(&config);
+                    let mut cache = ParseCache::default();
+                    (
+                        exit_on_error(generate_graph_at_revision(&config, &base, &mut cache)),
+                        exit_on_error(generate_graph_at_revision(&config, &head, &mut cache)),
+                    )
+                }
+                _ => (
+                    exit_on_error(read_graph(&old.unwrap())),
+                    exit_on_error(read_graph(&new.unwrap())),
+                ),
+            };
 
-            println!("Saving graph to JSON...");
-            let graph_json = serde_json::to_string_pretty(&graph).unwrap();
-            let cwd = std::env::current_dir().unwrap();
-            let output_path = cwd.join("graph.json");
-            fs::write(output_path, graph_json).unwrap();
+            let diff = diff_graphs(&old_graph, &new_graph);
+            save_output(&output, render_diff(&diff, format).as_bytes(), false);
+        }
+        Some(Commands::History {
+            config,
+            from,
+            to,
+            interval,
+            format,
+            output,
+        }) => {
+            if output == "-" {
+                set_quiet(true);
+            }
 
-            println!("Done!");
+            let config = load_config(&config);
+            let entries = exit_on_error(generate_history(&config, from.as_deref(), &to, interval));
+            save_output(&output, render_history(&entries, format).as_bytes(), false);
+        }
+        Some(Commands::Schema { output }) => {
+            save_output(&output, graph_json_schema().as_bytes(), false);
         }
         Some(Commands::Serve { config: _ }) => {
             panic!("Not implemented yet");
@@ -55,3 +354,42 @@ fn main() {
         }
     }
 }
+
+fn load_config(config_location: &str) -> Config {
+    progress!("Config location: {}", config_location);
+
+    let config = exit_on_error(
+        read_config_file(config_location)
+            .map_err(|error| format!("Couldn't read {}: {}", config_location, error)),
+    );
+    progress!("Language: {}", config.language);
+
+    config
+}
+
+/// Reads a graph file, saying which one when it can't be read.
+fn read_graph(file_path: &str) -> Result<Graph, String> {
+    read_graph_file(file_path).map_err(|error| format!("Couldn't read {}: {}", file_path, error))
+}
+
+/// Writes a command's output, exiting if it can't be written, e.g. to a
+/// directory that doesn't exist or to a pipe that was closed.
+fn save_output(path: &str, contents: &[u8], gzip: bool) {
+    let destination = if path == "-" { "stdout" } else { path };
+    exit_on_error(
+        write_output(path, contents, gzip)
+            .map_err(|error| format!("Couldn't write to {}: {}", destination, error)),
+    );
+}
+
+/// Unwraps the result of a step that fails because of the input rather than
+/// a bug, printing the error and exiting if it failed.
+fn exit_on_error<T, E: Display>(result: Result<T, E>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
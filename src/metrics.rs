@@ -0,0 +1,331 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{Graph, Node};
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct NodeMetrics {
+    /// Number of distinct modules that import this module (Ca)
+    pub afferent_coupling: usize,
+    /// Number of distinct modules this module imports (Ce)
+    pub efferent_coupling: usize,
+    /// Martin's instability, Ce / (Ca + Ce), from 0 (stable) to 1 (unstable)
+    pub instability: f64,
+    /// Number of modules reachable by following imports
+    pub transitive_dependencies: usize,
+    /// Length of the longest chain of imports starting at this module
+    pub depth: usize,
+}
+
+/// Adjacency lists for a graph, indexed by each node's position in `graph.nodes`.
+pub struct Adjacency {
+    pub outgoing: Vec<Vec<usize>>,
+    pub incoming: Vec<Vec<usize>>,
+}
+
+impl Adjacency {
+    pub fn new(graph: &Graph) -> Self {
+        let index_by_id: HashMap<&str, usize> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id.as_str(), index))
+            .collect();
+
+        let mut outgoing: Vec<HashSet<usize>> = vec![HashSet::new(); graph.nodes.len()];
+        let mut incoming: Vec<HashSet<usize>> = vec![HashSet::new(); graph.nodes.len()];
+        for edge in graph.edges.iter() {
+            let source = index_by_id.get(edge.source_id.as_str());
+            let target = index_by_id.get(edge.target_id.as_str());
+            if let (Some(&source), Some(&target)) = (source, target) {
+                outgoing[source].insert(target);
+                incoming[target].insert(source);
+            }
+        }
+
+        Adjacency {
+            outgoing: outgoing.into_iter().map(sorted).collect(),
+            incoming: incoming.into_iter().map(sorted).collect(),
+        }
+    }
+}
+
+fn sorted(set: HashSet<usize>) -> Vec<usize> {
+    let mut list: Vec<usize> = set.into_iter().collect();
+    list.sort();
+    list
+}
+
+pub fn compute_metrics(graph: &Graph) -> Vec<NodeMetrics> {
+    let adjacency = Adjacency::new(graph);
+    let components = strongly_connected_components(&adjacency);
+    let depths = longest_chain_depths(&adjacency, &components);
+
+    (0..graph.nodes.len())
+        .map(|index| {
+            let afferent_coupling = adjacency.incoming[index].len();
+            let efferent_coupling = adjacency.outgoing[index].len();
+            let instability = if afferent_coupling + efferent_coupling == 0 {
+                0.0
+            } else {
+                efferent_coupling as f64 / (afferent_coupling + efferent_coupling) as f64
+            };
+
+            NodeMetrics {
+                afferent_coupling,
+                efferent_coupling,
+                instability,
+                transitive_dependencies: count_reachable(&adjacency, index),
+                depth: depths[index],
+            }
+        })
+        .collect()
+}
+
+pub fn attach_metrics(graph: &mut Graph) {
+    let metrics = compute_metrics(graph);
+    for (node, node_metrics) in graph.nodes.iter_mut().zip(metrics) {
+        node.metrics = Some(node_metrics);
+    }
+}
+
+/// Returns the nodes with the most connections, most connected first.
+pub fn top_hubs(graph: &Graph, count: usize) -> Vec<(&Node, &NodeMetrics)> {
+    let mut hubs: Vec<(&Node, &NodeMetrics)> = graph
+        .nodes
+        .iter()
+        .filter_map(|node| node.metrics.as_ref().map(|metrics| (node, metrics)))
+        .collect();
+
+    hubs.sort_by(|(a_node, a), (b_node, b)| {
+        let a_total = a.afferent_coupling + a.efferent_coupling;
+        let b_total = b.afferent_coupling + b.efferent_coupling;
+        b_total
+            .cmp(&a_total)
+            .then_with(|| b.afferent_coupling.cmp(&a.afferent_coupling))
+            .then_with(|| a_node.label.cmp(&b_node.label))
+    });
+    hubs.truncate(count);
+
+    hubs
+}
+
+fn count_reachable(adjacency: &Adjacency, start: usize) -> usize {
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut stack: Vec<usize> = adjacency.outgoing[start].clone();
+
+    while let Some(index) = stack.pop() {
+        if index == start || !visited.insert(index) {
+            continue;
+        }
+        stack.extend(adjacency.outgoing[index].iter().copied());
+    }
+
+    visited.len()
+}
+
+/// Finds strongly connected components with Kosaraju's algorithm.
+///
+/// Components are returned in topological order of the condensed graph: every
+/// component only has edges into components that appear after it.
+pub fn strongly_connected_components(adjacency: &Adjacency) -> Vec<Vec<usize>> {
+    let node_count = adjacency.outgoing.len();
+
+    // First pass: order nodes by DFS finish time
+    let mut visited = vec![false; node_count];
+    let mut finish_order: Vec<usize> = Vec::with_capacity(node_count);
+    for start in 0..node_count {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack: Vec<(usize, usize)> = vec![(start, 0)];
+        while let Some((index, next_child)) = stack.pop() {
+            if let Some(&child) = adjacency.outgoing[index].get(next_child) {
+                stack.push((index, next_child + 1));
+                if !visited[child] {
+                    visited[child] = true;
+                    stack.push((child, 0));
+                }
+            } else {
+                finish_order.push(index);
+            }
+        }
+    }
+
+    // Second pass: collect components on the transposed graph
+    let mut assigned = vec![false; node_count];
+    let mut components: Vec<Vec<usize>> = vec![];
+    for &start in finish_order.iter().rev() {
+        if assigned[start] {
+            continue;
+        }
+        assigned[start] = true;
+        let mut component = vec![];
+        let mut stack = vec![start];
+        while let Some(index) = stack.pop() {
+            component.push(index);
+            for &parent in adjacency.incoming[index].iter() {
+                if !assigned[parent] {
+                    assigned[parent] = true;
+                    stack.push(parent);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+
+    components
+}
+
+/// Returns the components that form import cycles, including modules that import themselves.
+pub fn find_cycles(adjacency: &Adjacency) -> Vec<Vec<usize>> {
+    strongly_connected_components(adjacency)
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1 || adjacency.outgoing[component[0]].contains(&component[0])
+        })
+        .collect()
+}
+
+fn longest_chain_depths(adjacency: &Adjacency, components: &[Vec<usize>]) -> Vec<usize> {
+    let mut component_by_node = vec![0; adjacency.outgoing.len()];
+    for (component_index, component) in components.iter().enumerate() {
+        for &index in component.iter() {
+            component_by_node[index] = component_index;
+        }
+    }
+
+    // Components are in topological order, so walking them backwards visits
+    // every dependency before the modules that import it.
+    let mut component_depths = vec![0; components.len()];
+    for (component_index, component) in components.iter().enumerate().rev() {
+        let depth = component
+            .iter()
+            .flat_map(|&index| adjacency.outgoing[index].iter())
+            .map(|&target| component_by_node[target])
+            .filter(|&target_component| target_component != component_index)
+            .map(|target_component| component_depths[target_component] + 1)
+            .max()
+            .unwrap_or(0);
+        component_depths[component_index] = depth;
+    }
+
+    component_by_node
+        .iter()
+        .map(|&component_index| component_depths[component_index])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::{Edge, NodeType, SCHEMA_VERSION};
+
+    fn test_node(id: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            node_type: NodeType::Internal,
+            label: format!("{}.ts", id),
+            path_absolute: format!("/path/to/repo/{}.ts", id),
+            path_relative: format!("{}.ts", id),
+            metrics: None,
+            size: None,
+        }
+    }
+
+    fn test_edge(source_id: &str, target_id: &str) -> Edge {
+        Edge {
+            id: format!("{}->{}", source_id, target_id),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            weight: None,
+            kind: None,
+        }
+    }
+
+    fn test_graph(node_ids: &[&str], edges: &[(&str, &str)]) -> Graph {
+        Graph {
+            schema_version: SCHEMA_VERSION,
+            title: "test".to_string(),
+            language: None,
+            nodes: node_ids.iter().map(|id| test_node(id)).collect(),
+            edges: edges
+                .iter()
+                .map(|(source, target)| test_edge(source, target))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_compute_metrics_chain() {
+        let graph = test_graph(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+        let metrics = compute_metrics(&graph);
+
+        assert_eq!(metrics[0].afferent_coupling, 0);
+        assert_eq!(metrics[0].efferent_coupling, 1);
+        assert_eq!(metrics[0].instability, 1.0);
+        assert_eq!(metrics[0].transitive_dependencies, 2);
+        assert_eq!(metrics[0].depth, 2);
+
+        assert_eq!(metrics[1].instability, 0.5);
+        assert_eq!(metrics[1].depth, 1);
+
+        assert_eq!(metrics[2].afferent_coupling, 1);
+        assert_eq!(metrics[2].instability, 0.0);
+        assert_eq!(metrics[2].transitive_dependencies, 0);
+        assert_eq!(metrics[2].depth, 0);
+    }
+
+    #[test]
+    fn test_compute_metrics_ignores_duplicate_edges() {
+        let graph = test_graph(&["a", "b"], &[("a", "b"), ("a", "b")]);
+        let metrics = compute_metrics(&graph);
+
+        assert_eq!(metrics[0].efferent_coupling, 1);
+        assert_eq!(metrics[1].afferent_coupling, 1);
+    }
+
+    #[test]
+    fn test_compute_metrics_cycle() {
+        let graph = test_graph(
+            &["a", "b", "c", "d"],
+            &[("a", "b"), ("b", "c"), ("c", "b"), ("c", "d")],
+        );
+        let metrics = compute_metrics(&graph);
+
+        assert_eq!(metrics[0].transitive_dependencies, 3);
+        assert_eq!(metrics[0].depth, 2);
+        assert_eq!(metrics[1].transitive_dependencies, 2);
+        assert_eq!(metrics[1].depth, 1);
+        assert_eq!(metrics[2].depth, 1);
+        assert_eq!(metrics[3].depth, 0);
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let graph = test_graph(
+            &["a", "b", "c", "d"],
+            &[("a", "b"), ("b", "a"), ("b", "c"), ("d", "d")],
+        );
+        let adjacency = Adjacency::new(&graph);
+        let mut cycles = find_cycles(&adjacency);
+        cycles.sort();
+
+        assert_eq!(cycles, vec![vec![0, 1], vec![3]]);
+    }
+
+    #[test]
+    fn test_top_hubs() {
+        let mut graph = test_graph(&["a", "b", "c", "d"], &[("a", "c"), ("b", "c"), ("c", "d")]);
+        attach_metrics(&mut graph);
+
+        let hubs = top_hubs(&graph, 2);
+        let labels: Vec<&str> = hubs.iter().map(|(node, _)| node.label.as_str()).collect();
+
+        assert_eq!(labels, vec!["c.ts", "d.ts"]);
+    }
+}
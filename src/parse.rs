@@ -1,16 +1,157 @@
-use tree_sitter::{Parser, Query, QueryCursor, Tree};
-use tree_sitter_typescript;
+use std::collections::HashMap;
+use std::path::Path;
+use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
 
 use crate::imports::UnresolvedImport;
+use crate::sfc::{extract_script_blocks, ScriptBlock};
 
-pub fn extract_imports(file_contents: &str) -> Vec<UnresolvedImport> {
+/// Remembers what was parsed from each file, keyed by an id of the file's
+/// contents, so unchanged files aren't read and parsed again.
+#[derive(Default)]
+pub struct ParseCache {
+    parsed_files_by_content_id: HashMap<String, ParsedFile>,
+}
+
+impl ParseCache {
+    pub fn get(&self, content_id: &str) -> Option<&ParsedFile> {
+        self.parsed_files_by_content_id.get(content_id)
+    }
+
+    pub fn insert(&mut self, content_id: String, parsed_file: ParsedFile) {
+        self.parsed_files_by_content_id
+            .insert(content_id, parsed_file);
+    }
+}
+
+/// The extension of a file in lowercase, as the files to analyze are picked
+/// by extension in any case, e.g. `App.Vue` along with `App.vue`.
+pub fn file_extension(path_relative: &str) -> Option<String> {
+    Path::new(path_relative)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase())
+}
+
+/// The tree-sitter grammar a source file is parsed with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Grammar {
+    TypeScript,
+    Tsx,
+    JavaScript,
+}
+
+impl Grammar {
+    /// Picks the grammar from the file's extension. Unknown extensions are
+    /// parsed as TSX, which accepts the most syntax.
+    pub fn for_path(path_relative: &str) -> Grammar {
+        match file_extension(path_relative).as_deref() {
+            Some("ts" | "mts" | "cts") => Grammar::TypeScript,
+            Some("js" | "jsx" | "mjs" | "cjs") => Grammar::JavaScript,
+            _ => Grammar::Tsx,
+        }
+    }
+
+    fn language(&self) -> Language {
+        match self {
+            Grammar::TypeScript => tree_sitter_typescript::language_typescript(),
+            Grammar::Tsx => tree_sitter_typescript::language_tsx(),
+            Grammar::JavaScript => tree_sitter_javascript::language(),
+        }
+    }
+}
+
+/// The imports found in a file, and any syntax errors the parser ran into.
+#[derive(Clone)]
+pub struct ParsedFile {
+    pub imports: Vec<UnresolvedImport>,
+    pub warnings: Vec<ParseWarning>,
+    /// The package the file declares, for languages where it needn't match
+    /// the file's directory, like Kotlin
+    pub package: Option<String>,
+}
+
+/// A syntax error in a source file. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Parses a source file with the grammar for its extension. For Vue and
+/// Svelte components, only the `<script>` blocks are parsed.
+pub fn parse_source_file(path_relative: &str, file_contents: &str) -> ParsedFile {
+    match file_extension(path_relative).as_deref() {
+        Some("vue") => parse_component_file(file_contents, vue_block_grammar),
+        // Svelte scripts are plain JavaScript or TypeScript without JSX, which
+        // the TypeScript grammar handles either way
+        Some("svelte") => parse_component_file(file_contents, |_| Grammar::TypeScript),
+        _ => parse_file(file_contents, Grammar::for_path(path_relative)),
+    }
+}
+
+/// Vue blocks are parsed with the grammar their `lang` attribute asks for,
+/// and JavaScript by default.
+fn vue_block_grammar(block: &ScriptBlock) -> Grammar {
+    match block.attributes.get("lang").map(String::as_str) {
+        Some("ts") => Grammar::TypeScript,
+        Some("tsx") => Grammar::Tsx,
+        _ => Grammar::JavaScript,
+    }
+}
+
+/// Parses each `<script>` block of a component file, e.g. `<script setup>` in
+/// Vue or `<script context="module">` in Svelte.
+fn parse_component_file(
+    file_contents: &str,
+    block_grammar: fn(&ScriptBlock) -> Grammar,
+) -> ParsedFile {
+    let mut imports = vec![];
+    let mut warnings = vec![];
+
+    for block in extract_script_blocks(file_contents) {
+        if let Some(source) = block.attributes.get("src") {
+            imports.push(UnresolvedImport {
+                module_name: source.clone(),
+            });
+        }
+
+        let parsed_block = parse_file(&block.contents, block_grammar(&block));
+
+        imports.extend(parsed_block.imports);
+        warnings.extend(parsed_block.warnings.into_iter().map(|warning| {
+            let (line, column) = block.file_position(warning.line, warning.column);
+            ParseWarning {
+                line,
+                column,
+                ..warning
+            }
+        }));
+    }
+
+    ParsedFile {
+        imports,
+        warnings,
+        package: None,
+    }
+}
+
+pub fn parse_file(file_contents: &str, grammar: Grammar) -> ParsedFile {
+    let tree = parse_tree(file_contents, grammar.language());
+
+    ParsedFile {
+        imports: extract_imports(&tree, grammar, file_contents),
+        warnings: find_syntax_errors(&tree, file_contents),
+        package: None,
+    }
+}
+
+fn extract_imports(tree: &Tree, grammar: Grammar, file_contents: &str) -> Vec<UnresolvedImport> {
     if !file_contents.contains("import") {
         return vec![];
     }
 
-    let tree = parse_ts_file(file_contents);
-
-    let language = tree_sitter_typescript::language_tsx();
+    let language = grammar.language();
 
     let query_contents = r###"
             (
@@ -44,15 +185,158 @@ pub fn extract_imports(file_contents: &str) -> Vec<UnresolvedImport> {
     module_imports
 }
 
-fn parse_ts_file(file_contents: &str) -> Tree {
-    let language = tree_sitter_typescript::language_tsx();
+/// Collects the ERROR and MISSING nodes in the tree. Errors nested inside
+/// another error aren't reported separately.
+pub fn find_syntax_errors(tree: &Tree, file_contents: &str) -> Vec<ParseWarning> {
+    let mut warnings = vec![];
+    if !tree.root_node().has_error() {
+        return warnings;
+    }
+
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        let is_syntax_error = node.is_error() || node.is_missing();
+        if is_syntax_error {
+            let position = node.start_position();
+            let message = if node.is_missing() {
+                format!("missing `{}`", node.kind())
+            } else {
+                let text = node_text(node, file_contents);
+                let first_line = text.lines().next().unwrap_or_default();
+                format!("unexpected `{}`", first_line.trim())
+            };
+            warnings.push(ParseWarning {
+                line: position.row + 1,
+                column: position.column + 1,
+                message,
+            });
+        }
+
+        if !is_syntax_error && node.has_error() && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return warnings;
+            }
+        }
+    }
+}
+
+fn parse_tree(file_contents: &str, language: Language) -> Tree {
     let mut parser = Parser::new();
     parser.set_language(language).unwrap();
-    let tree = parser.parse(file_contents, None).unwrap();
 
-    tree
+    parser.parse(file_contents, None).unwrap()
+}
+
+pub fn node_text(node: tree_sitter::Node, src: &str) -> String {
+    src[node.start_byte()..node.end_byte()].to_string()
 }
 
-fn node_text(node: tree_sitter::Node, src: &str) -> String {
-    return src[node.start_byte()..node.end_byte()].to_string();
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn module_names(parsed_file: &ParsedFile) -> Vec<String> {
+        parsed_file
+            .imports
+            .iter()
+            .map(|import| import.module_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_grammar_for_path() {
+        assert_eq!(Grammar::for_path("src/a.ts"), Grammar::TypeScript);
+        assert_eq!(Grammar::for_path("src/a.cts"), Grammar::TypeScript);
+        assert_eq!(Grammar::for_path("src/a.tsx"), Grammar::Tsx);
+        assert_eq!(Grammar::for_path("src/a.jsx"), Grammar::JavaScript);
+        assert_eq!(Grammar::for_path("src/a.mjs"), Grammar::JavaScript);
+    }
+
+    #[test]
+    fn test_extract_imports() {
+        let typescript = r#"
+            import { a } from "./a";
+            const identity = <T>(value: T) => value;
+            const b = <Foo>bar;
+            import "./b";
+        "#;
+        assert_eq!(
+            module_names(&parse_file(typescript, Grammar::TypeScript)),
+            vec!["./a", "./b"]
+        );
+
+        let javascript = r#"
+            import React from "react";
+            export const App = () => <div />;
+        "#;
+        assert_eq!(
+            module_names(&parse_file(javascript, Grammar::JavaScript)),
+            vec!["react"]
+        );
+    }
+
+    #[test]
+    fn test_parse_file_reports_syntax_errors() {
+        let valid = "import { a } from \"./a\";\nconst identity = <T>(value: T) => value;\n";
+        assert!(parse_file(valid, Grammar::TypeScript).warnings.is_empty());
+
+        let invalid = "import { a } from \"./a\";\n\nconst b = (;\n";
+        let parsed_file = parse_file(invalid, Grammar::TypeScript);
+
+        assert_eq!(module_names(&parsed_file), vec!["./a"]);
+        assert_eq!(parsed_file.warnings.len(), 1);
+        assert_eq!(parsed_file.warnings[0].line, 3);
+    }
+
+    #[test]
+    fn test_parse_vue_file() {
+        let file_contents = r#"<template>
+  <Child />
+</template>
+
+<script lang="ts">
+import { defineComponent } from "vue";
+const identity = <T>(value: T) => value;
+</script>
+
+<script setup>
+import Child from "./Child.vue";
+const broken = (;
+</script>
+"#;
+
+        let parsed_file = parse_source_file("src/App.vue", file_contents);
+
+        assert_eq!(module_names(&parsed_file), vec!["vue", "./Child.vue"]);
+        assert_eq!(parsed_file.warnings.len(), 1);
+        assert_eq!(parsed_file.warnings[0].line, 12);
+    }
+
+    #[test]
+    fn test_parse_svelte_file() {
+        let file_contents = r#"<script context="module" lang="ts">
+  export const prerender = true;
+  import type { Load } from "./types";
+</script>
+
+<script>
+  import Button from "./Button.svelte";
+  export let name: string;
+</script>
+
+<Button on:click={() => (name = "world")}>{name}</Button>
+"#;
+
+        let parsed_file = parse_source_file("src/Page.svelte", file_contents);
+
+        assert_eq!(
+            module_names(&parsed_file),
+            vec!["./types", "./Button.svelte"]
+        );
+        assert!(parsed_file.warnings.is_empty());
+    }
 }
@@ -0,0 +1,23 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silences progress messages, e.g. when the graph itself is written to stdout.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints a progress message, like `println!`, unless quiet mode is enabled.
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::progress::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use progress;
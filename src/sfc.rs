@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+/// A `<script>` block in a single-file component, like a `.vue` or `.svelte` file.
+#[derive(Debug, PartialEq)]
+pub struct ScriptBlock {
+    pub contents: String,
+    pub attributes: HashMap<String, String>,
+    /// Line of the file the contents start on, starting at 0
+    pub start_line: usize,
+    /// Column of the file the contents start at, starting at 0
+    pub start_column: usize,
+}
+
+impl ScriptBlock {
+    /// Converts a line and column inside the block, starting at 1, to the
+    /// same position in the whole file.
+    pub fn file_position(&self, line: usize, column: usize) -> (usize, usize) {
+        if line == 1 {
+            (self.start_line + 1, self.start_column + column)
+        } else {
+            (self.start_line + line, column)
+        }
+    }
+}
+
+/// Finds the top-level `<script>` blocks in a component file.
+///
+/// This isn't a full HTML parser: it looks for `<script ...>` tags and the
+/// next `</script>` after each, which is enough for component files.
+pub fn extract_script_blocks(file_contents: &str) -> Vec<ScriptBlock> {
+    let mut blocks = vec![];
+    let mut position = 0;
+
+    while let Some(offset) = file_contents[position..].find("<script") {
+        let tag_start = position + offset;
+        let after_name = tag_start + "<script".len();
+        let is_script_tag = file_contents[after_name..]
+            .chars()
+            .next()
+            .is_some_and(|next| next == '>' || next == '/' || next.is_whitespace());
+        if !is_script_tag {
+            position = after_name;
+            continue;
+        }
+
+        let Some(tag_end) = find_tag_end(file_contents, after_name) else {
+            break;
+        };
+        let attributes = parse_attributes(&file_contents[after_name..tag_end]);
+        let contents_start = tag_end + 1;
+
+        if file_contents[..tag_end].ends_with('/') {
+            // A self-closing tag, e.g. `<script src="./a.js" />`
+            blocks.push(script_block(file_contents, "", attributes, contents_start));
+            position = contents_start;
+            continue;
+        }
+
+        let contents_end = file_contents[contents_start..]
+            .find("</script")
+            .map(|offset| contents_start + offset)
+            .unwrap_or(file_contents.len());
+        blocks.push(script_block(
+            file_contents,
+            &file_contents[contents_start..contents_end],
+            attributes,
+            contents_start,
+        ));
+        position = contents_end;
+    }
+
+    blocks
+}
+
+fn script_block(
+    file_contents: &str,
+    contents: &str,
+    attributes: HashMap<String, String>,
+    contents_start: usize,
+) -> ScriptBlock {
+    let before = &file_contents[..contents_start];
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    ScriptBlock {
+        contents: contents.to_string(),
+        attributes,
+        start_line: before.matches('\n').count(),
+        start_column: before[line_start..].chars().count(),
+    }
+}
+
+/// Finds the `>` that closes a tag, skipping any inside quoted attribute values.
+fn find_tag_end(file_contents: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (offset, character) in file_contents[start..].char_indices() {
+        match (quote, character) {
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if open == character => quote = None,
+            (None, '>') => return Some(start + offset),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Parses attributes like `lang="ts"`, `lang='ts'`, `lang=ts` and `setup`.
+/// Attributes without a value map to an empty string.
+fn parse_attributes(tag_contents: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = tag_contents.trim_start();
+
+    while !rest.is_empty() && rest != "/" {
+        let name_end = rest
+            .find(|character: char| character == '=' || character.is_whitespace())
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].trim_end_matches('/').to_string();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+            match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let value_end = after_equals[1..]
+                        .find(quote)
+                        .map(|offset| offset + 1)
+                        .unwrap_or(after_equals.len());
+                    value = after_equals[1..value_end].to_string();
+                    rest = after_equals.get(value_end + 1..).unwrap_or_default();
+                }
+                _ => {
+                    let value_end = after_equals
+                        .find(char::is_whitespace)
+                        .unwrap_or(after_equals.len());
+                    value = after_equals[..value_end].trim_end_matches('/').to_string();
+                    rest = &after_equals[value_end..];
+                }
+            }
+        }
+
+        if !name.is_empty() {
+            attributes.insert(name, value);
+        }
+        rest = rest.trim_start();
+    }
+
+    attributes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_script_blocks() {
+        let file_contents = r#"<template>
+  <div>{{ message }}</div>
+</template>
+
+<script lang="ts">
+import { defineComponent } from "vue";
+</script>
+<script setup lang='ts'>import Child from "./Child.vue";</script>
+<style>
+.a { color: red; }
+</style>
+"#;
+
+        let blocks = extract_script_blocks(file_contents);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[0].contents,
+            "\nimport { defineComponent } from \"vue\";\n"
+        );
+        assert_eq!(blocks[0].attributes.get("lang"), Some(&"ts".to_string()));
+        assert_eq!(blocks[0].start_line, 4);
+        assert_eq!(blocks[0].file_position(2, 1), (6, 1));
+
+        assert_eq!(blocks[1].contents, "import Child from \"./Child.vue\";");
+        assert_eq!(blocks[1].attributes.get("setup"), Some(&"".to_string()));
+        assert_eq!(blocks[1].attributes.get("lang"), Some(&"ts".to_string()));
+        assert_eq!(blocks[1].file_position(1, 8), (8, 32));
+    }
+
+    #[test]
+    fn test_extract_script_blocks_ignores_other_tags() {
+        let file_contents = "<scripts></scripts>\n<script src=\"./a.js\" />\n";
+
+        let blocks = extract_script_blocks(file_contents);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].contents, "");
+        assert_eq!(blocks[0].attributes.get("src"), Some(&"./a.js".to_string()));
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let attributes = parse_attributes(r#" context="module" lang=ts async data-x='a > b'"#);
+
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes["context"], "module");
+        assert_eq!(attributes["lang"], "ts");
+        assert_eq!(attributes["async"], "");
+        assert_eq!(attributes["data-x"], "a > b");
+    }
+}
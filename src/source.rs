@@ -0,0 +1,359 @@
+use glob::{Pattern, PatternError};
+use ignore::WalkBuilder;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::Config;
+use crate::language::LanguageBackend;
+
+/// A snapshot of the project's files that the graph is built from.
+pub trait SourceTree {
+    /// Returns the paths of the source files to analyze, relative to the
+    /// project root, leaving out ignored files and the ones `filter` rejects.
+    fn source_paths(&self, filter: &SourceFilter) -> Vec<String>;
+
+    /// Lists every file matching a glob pattern, relative to the project root,
+    /// whether or not it's a source file, e.g. to find manifests.
+    fn find_files(&self, pattern: &str) -> Result<Vec<String>, PatternError>;
+
+    /// Reads a file, given its path relative to the project root.
+    fn read_file(&self, path_relative: &str) -> io::Result<String>;
+
+    /// Returns the size of a file in bytes, without reading it.
+    fn file_size(&self, path_relative: &str) -> io::Result<u64>;
+
+    /// Returns an id that changes whenever the file's contents do, if one is
+    /// available without reading the file.
+    fn content_id(&self, _path_relative: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Decides which files in the project are analyzed: files with one of the
+/// configured extensions in any case, outside `node_modules` and the
+/// language's other skipped directories, matching the `include` patterns (if
+/// any) and none of the `exclude` patterns from the config.
+///
+/// A pattern matches a file if it matches the file's path relative to the
+/// project root, or the path of one of its parent directories, so `coverage`
+/// excludes everything inside `coverage/`.
+#[derive(Clone)]
+pub struct SourceFilter {
+    extensions: Vec<String>,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    /// Names of directories skipped wherever they are, besides `node_modules`
+    skipped_directories: Vec<String>,
+}
+
+impl SourceFilter {
+    pub fn new(
+        extensions: Vec<String>,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self, PatternError> {
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>, PatternError> {
+            patterns
+                .iter()
+                .map(|pattern| Pattern::new(pattern))
+                .collect()
+        };
+
+        Ok(SourceFilter {
+            extensions,
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+            skipped_directories: vec![],
+        })
+    }
+
+    pub fn from_config(
+        config: &Config,
+        backend: &dyn LanguageBackend,
+    ) -> Result<Self, PatternError> {
+        let mut filter = SourceFilter::new(
+            backend.source_extensions().to_vec(),
+            config.include.as_deref().unwrap_or_default(),
+            config.exclude.as_deref().unwrap_or_default(),
+        )?;
+        filter.skipped_directories = backend
+            .skipped_directories()
+            .iter()
+            .map(|directory| directory.to_string())
+            .collect();
+
+        Ok(filter)
+    }
+
+    /// Whether the file at this path, relative to the project root, should be analyzed.
+    pub fn matches(&self, path_relative: &str) -> bool {
+        let path = Path::new(path_relative);
+        let has_source_extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                self.extensions
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(extension))
+            });
+
+        has_source_extension
+            && !self.is_in_skipped_directory(path)
+            && (self.include.is_empty() || matches_any(&self.include, path))
+            && !matches_any(&self.exclude, path)
+    }
+
+    /// Whether everything inside this directory is excluded, so it needn't be listed.
+    fn skips_directory(&self, path_relative: &Path) -> bool {
+        self.is_in_skipped_directory(path_relative) || matches_any(&self.exclude, path_relative)
+    }
+
+    fn is_in_skipped_directory(&self, path: &Path) -> bool {
+        path.iter().any(|component| {
+            component == "node_modules"
+                || self
+                    .skipped_directories
+                    .iter()
+                    .any(|directory| component == directory.as_str())
+        })
+    }
+}
+
+/// Whether any of the patterns match the path or one of its parent directories.
+fn matches_any(patterns: &[Pattern], path: &Path) -> bool {
+    path.ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches_path(ancestor))
+        })
+}
+
+/// The files currently on disk in the project directory.
+///
+/// Files ignored by `.gitignore` or `.ignore` files are left out.
+pub struct WorkingDirectory {
+    root_directory: String,
+}
+
+impl WorkingDirectory {
+    pub fn new(root_directory: &str) -> Self {
+        WorkingDirectory {
+            root_directory: root_directory.to_string(),
+        }
+    }
+}
+
+impl SourceTree for WorkingDirectory {
+    fn source_paths(&self, filter: &SourceFilter) -> Vec<String> {
+        let root_directory = Path::new(&self.root_directory).to_path_buf();
+        let directory_filter = filter.clone();
+
+        let mut paths: Vec<String> = WalkBuilder::new(&root_directory)
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(move |entry| {
+                let path_relative = entry.path().strip_prefix(&root_directory).unwrap();
+                let is_directory = entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir());
+                !(is_directory
+                    && (entry.file_name() == ".git"
+                        || directory_filter.skips_directory(path_relative)))
+            })
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file())
+            })
+            .filter_map(|entry| {
+                let path_relative = entry.path().strip_prefix(&self.root_directory).ok()?;
+                Some(path_relative.to_str()?.to_string())
+            })
+            .filter(|path_relative| filter.matches(path_relative))
+            .collect();
+        paths.sort();
+
+        paths
+    }
+
+    fn find_files(&self, pattern: &str) -> Result<Vec<String>, PatternError> {
+        let root_directory = Path::new(&self.root_directory);
+        let pattern = root_directory.join(pattern);
+        let paths = glob::glob(pattern.to_str().unwrap())?
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                let path_relative = path.strip_prefix(root_directory).ok()?;
+                Some(path_relative.to_str()?.to_string())
+            })
+            .collect();
+
+        Ok(paths)
+    }
+
+    fn read_file(&self, path_relative: &str) -> io::Result<String> {
+        fs::read_to_string(Path::new(&self.root_directory).join(path_relative))
+    }
+
+    fn file_size(&self, path_relative: &str) -> io::Result<u64> {
+        Ok(fs::metadata(Path::new(&self.root_directory).join(path_relative))?.len())
+    }
+}
+
+/// Resolves `.` and `..` components without touching the filesystem, so
+/// paths that don't exist on disk (e.g. in a git revision) work too.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Files held in memory, for building graphs without touching the disk.
+#[cfg(test)]
+pub struct MemorySourceTree {
+    pub files: Vec<(&'static str, &'static str)>,
+}
+
+#[cfg(test)]
+impl SourceTree for MemorySourceTree {
+    fn source_paths(&self, filter: &SourceFilter) -> Vec<String> {
+        self.files
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .filter(|path| filter.matches(path))
+            .collect()
+    }
+
+    fn find_files(&self, pattern: &str) -> Result<Vec<String>, PatternError> {
+        let pattern = Pattern::new(pattern)?;
+        Ok(self
+            .files
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .filter(|path| pattern.matches(path))
+            .collect())
+    }
+
+    fn read_file(&self, path_relative: &str) -> io::Result<String> {
+        self.files
+            .iter()
+            .find(|(path, _)| *path == path_relative)
+            .map(|(_, contents)| contents.to_string())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn file_size(&self, path_relative: &str) -> io::Result<u64> {
+        Ok(self.read_file(path_relative)?.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::language::typescript::DEFAULT_EXTENSIONS;
+    use uuid::Uuid;
+
+    fn default_extensions() -> Vec<String> {
+        patterns(&DEFAULT_EXTENSIONS)
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|pattern| pattern.to_string()).collect()
+    }
+
+    #[test]
+    fn test_source_filter() {
+        let filter = SourceFilter::new(
+            default_extensions(),
+            &[],
+            &patterns(&["coverage", "**/*.generated.ts"]),
+        )
+        .unwrap();
+
+        assert!(filter.matches("src/index.ts"));
+        assert!(filter.matches("src/build/index.tsx"));
+        assert!(filter.matches("scripts/build.mjs"));
+        assert!(!filter.matches("src/README.md"));
+        assert!(!filter.matches("node_modules/react/index.js"));
+        assert!(!filter.matches("coverage/lcov-report/sorter.js"));
+        assert!(!filter.matches("src/api.generated.ts"));
+
+        let filter = SourceFilter::new(
+            default_extensions(),
+            &patterns(&["src", "scripts/*.js"]),
+            &[],
+        )
+        .unwrap();
+
+        assert!(filter.matches("src/components/App.tsx"));
+        assert!(filter.matches("scripts/release.js"));
+        assert!(!filter.matches("storybook-static/main.js"));
+
+        assert!(SourceFilter::new(default_extensions(), &patterns(&["src/[a"]), &[]).is_err());
+
+        let filter = SourceFilter::new(patterns(&["ts"]), &[], &[]).unwrap();
+
+        assert!(filter.matches("src/index.ts"));
+        assert!(!filter.matches("src/index.js"));
+
+        // Extensions match in any case, e.g. images saved as `.PNG`
+        let filter = SourceFilter::new(patterns(&["png"]), &[], &[]).unwrap();
+
+        assert!(filter.matches("src/Logo.PNG"));
+    }
+
+    #[test]
+    fn test_working_directory_respects_ignore_files() {
+        let root = std::env::temp_dir().join(format!("depdoc-test-{}", Uuid::new_v4()));
+        for path in [
+            "src/index.ts",
+            "src/build/index.ts",
+            "src/gen/api.ts",
+            "coverage/sorter.js",
+            ".next/server.js",
+            "node_modules/react/index.js",
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::write(
+            root.join(".gitignore"),
+            "coverage/
+.next/
+",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/.ignore"),
+            "gen/
+",
+        )
+        .unwrap();
+
+        let filter = SourceFilter::new(default_extensions(), &[], &[]).unwrap();
+        let source = WorkingDirectory::new(root.to_str().unwrap());
+
+        assert_eq!(
+            source.source_paths(&filter),
+            vec!["src/build/index.ts", "src/index.ts"]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
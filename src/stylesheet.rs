@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::ModuleResolutionItem;
+use crate::graph::Node;
+use crate::imports::{ResolvedImport, UnresolvedImport};
+use crate::parse::file_extension;
+use crate::source::normalize_path;
+
+/// Extensions of the stylesheets read along with the code, in the order
+/// they're tried when an import leaves out the extension.
+pub const STYLESHEET_EXTENSIONS: [&str; 4] = ["scss", "sass", "less", "css"];
+
+/// At-rules that load another stylesheet.
+const IMPORT_RULES: [&str; 3] = ["@import", "@use", "@forward"];
+
+pub fn is_stylesheet(path_relative: &str) -> bool {
+    file_extension(path_relative)
+        .is_some_and(|extension| STYLESHEET_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Finds the stylesheets loaded by `@import`, `@use` and `@forward` rules in
+/// a CSS, SCSS, Sass or LESS file.
+///
+/// This isn't a full parser: it skips comments and reads the strings (or
+/// `url(...)`s) right after each rule, which is all these rules can load.
+pub fn parse_stylesheet_imports(file_contents: &str) -> Vec<UnresolvedImport> {
+    let contents = strip_comments(file_contents);
+    let mut imports: Vec<UnresolvedImport> = vec![];
+
+    let mut position = 0;
+    while let Some(offset) = contents[position..].find('@') {
+        let rule_start = position + offset;
+        position = rule_start + 1;
+
+        let Some(rule) = IMPORT_RULES.iter().find(|rule| {
+            contents[rule_start..].starts_with(**rule)
+                && !contents[rule_start + rule.len()..]
+                    .starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_')
+        }) else {
+            continue;
+        };
+
+        let mut rest = &contents[rule_start + rule.len()..];
+        loop {
+            rest = rest.trim_start();
+            // LESS import options, e.g. `@import (reference) "a.less";`
+            if rest.starts_with('(') {
+                let Some(options_end) = rest.find(')') else {
+                    break;
+                };
+                rest = rest[options_end + 1..].trim_start();
+            }
+
+            let Some((module_name, after)) = read_import_target(rest) else {
+                break;
+            };
+            if !imports
+                .iter()
+                .any(|import| import.module_name == module_name)
+            {
+                imports.push(UnresolvedImport { module_name });
+            }
+
+            // Only `@import` can list several stylesheets
+            rest = after.trim_start();
+            match rest.strip_prefix(',') {
+                Some(after_comma) if *rule == "@import" => rest = after_comma,
+                _ => break,
+            }
+        }
+    }
+
+    imports
+}
+
+/// Reads a quoted string or a `url(...)`, returning its contents and the text after it.
+fn read_import_target(text: &str) -> Option<(String, &str)> {
+    if let Some(after_url) = text.strip_prefix("url(") {
+        let url_end = after_url.find(')')?;
+        let url = after_url[..url_end]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'');
+        return Some((url.to_string(), &after_url[url_end + 1..]));
+    }
+
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let string_end = text[1..].find(quote)? + 1;
+    Some((text[1..string_end].to_string(), &text[string_end + 1..]))
+}
+
+/// Blanks out `/* */` comments, and `//` comments that start a line or follow
+/// whitespace, so that `url(http://...)` is left alone.
+fn strip_comments(file_contents: &str) -> String {
+    let mut stripped = String::with_capacity(file_contents.len());
+    let mut characters = file_contents.chars().peekable();
+    let mut previous = '\n';
+
+    while let Some(character) = characters.next() {
+        match (character, characters.peek()) {
+            ('/', Some('*')) => {
+                characters.next();
+                let mut last = ' ';
+                for comment_character in characters.by_ref() {
+                    if last == '*' && comment_character == '/' {
+                        break;
+                    }
+                    last = comment_character;
+                }
+                stripped.push(' ');
+                previous = ' ';
+            }
+            ('/', Some('/')) if previous.is_whitespace() => {
+                for comment_character in characters.by_ref() {
+                    if comment_character == '\n' {
+                        break;
+                    }
+                }
+                stripped.push('\n');
+                previous = '\n';
+            }
+            _ => {
+                stripped.push(character);
+                previous = character;
+            }
+        }
+    }
+
+    stripped
+}
+
+/// Works out which stylesheet an import from the stylesheet `node` loads.
+///
+/// Paths are relative to the importing file, or go through the config's
+/// module resolution. `@use "sass:math"` and packages like `~bootstrap/scss`
+/// are external, and URLs aren't modules at all.
+pub fn resolve_stylesheet_import(
+    import: UnresolvedImport,
+    node: &Node,
+    nodes_by_path: &HashMap<String, &Node>,
+    module_resolution: &Option<Vec<ModuleResolutionItem>>,
+) -> Option<ResolvedImport> {
+    let module_name = import.module_name;
+    if module_name.contains("://") || module_name.starts_with("//") {
+        return None;
+    }
+    if module_name.starts_with("sass:") {
+        return Some(ResolvedImport::ExternalImport {
+            target_module_name: module_name,
+        });
+    }
+
+    let mut module_paths = vec![];
+    if !module_name.starts_with('~') {
+        let directory = Path::new(&node.path_relative).parent().unwrap();
+        module_paths.push(directory.join(&module_name));
+    }
+    for resolution_item in module_resolution.iter().flatten() {
+        if module_name.starts_with(&resolution_item.pattern) {
+            module_paths.push(PathBuf::from(
+                module_name.replace(&resolution_item.pattern, &resolution_item.replacement),
+            ));
+        }
+    }
+
+    for module_path in module_paths {
+        for candidate in create_stylesheet_candidates(&normalize_path(&module_path)) {
+            let candidate = candidate.to_str().unwrap();
+            if let Some(target_node) = nodes_by_path.get(candidate) {
+                if target_node.id == node.id {
+                    return None;
+                }
+                return Some(ResolvedImport::InternalImport {
+                    target_path: candidate.to_string(),
+                    target_node_id: target_node.id.clone(),
+                });
+            }
+        }
+    }
+
+    Some(ResolvedImport::ExternalImport {
+        target_module_name: module_name.trim_start_matches('~').to_string(),
+    })
+}
+
+/// Lists the files a stylesheet import could refer to: the path itself, the
+/// path with each stylesheet extension, the partial `_name` of each, then an
+/// `_index` or `index` file inside it.
+fn create_stylesheet_candidates(module_path: &Path) -> Vec<PathBuf> {
+    let file_name = module_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .unwrap_or_default();
+    let partial_path = module_path.with_file_name(format!("_{}", file_name));
+
+    if is_stylesheet(module_path.to_str().unwrap()) {
+        return vec![module_path.to_path_buf(), partial_path];
+    }
+
+    let mut candidates = vec![];
+    for extension in STYLESHEET_EXTENSIONS {
+        candidates.push(PathBuf::from(format!(
+            "{}.{}",
+            module_path.display(),
+            extension
+        )));
+        candidates.push(PathBuf::from(format!(
+            "{}.{}",
+            partial_path.display(),
+            extension
+        )));
+    }
+    for extension in STYLESHEET_EXTENSIONS {
+        candidates.push(module_path.join(format!("_index.{}", extension)));
+        candidates.push(module_path.join(format!("index.{}", extension)));
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graph::NodeType;
+    use crate::language::{resolve_test_import, test_nodes};
+
+    #[test]
+    fn test_parse_stylesheet_imports() {
+        let file_contents = r#"
+@use "sass:math";
+@use 'theme/colors' as c;
+@forward "mixins" show button;
+/* @import "commented-out"; */
+// @import "also-commented-out";
+@import "reset", 'typography.css';
+@import url("https://fonts.example.com/css?family=Inter");
+@import (reference) "variables.less";
+@include button-reset;
+.a { background: url(http://example.com/a.png); }
+"#;
+
+        let module_names: Vec<String> = parse_stylesheet_imports(file_contents)
+            .into_iter()
+            .map(|import| import.module_name)
+            .collect();
+
+        assert_eq!(
+            module_names,
+            vec![
+                "sass:math",
+                "theme/colors",
+                "mixins",
+                "reset",
+                "typography.css",
+                "https://fonts.example.com/css?family=Inter",
+                "variables.less",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_stylesheet_import() {
+        let paths = [
+            "src/styles/main.scss",
+            "src/styles/_variables.scss",
+            "src/styles/theme/_index.scss",
+            "src/styles/reset.css",
+            "src/components/Button.module.scss",
+        ];
+        let nodes = test_nodes(&paths, |_| NodeType::Stylesheet);
+        let module_resolution = Some(vec![ModuleResolutionItem {
+            pattern: "@styles/".to_string(),
+            replacement: "src/styles/".to_string(),
+        }]);
+
+        let resolve = |from: usize, module_name: &str| {
+            resolve_test_import(&nodes, from, module_name, |import, node, nodes_by_path| {
+                resolve_stylesheet_import(import, node, nodes_by_path, &module_resolution)
+            })
+        };
+
+        let some = |target: &str| Some(target.to_string());
+        assert_eq!(resolve(0, "variables"), some("src/styles/_variables.scss"));
+        assert_eq!(
+            resolve(0, "_variables.scss"),
+            some("src/styles/_variables.scss")
+        );
+        assert_eq!(resolve(0, "theme"), some("src/styles/theme/_index.scss"));
+        assert_eq!(resolve(0, "reset.css"), some("src/styles/reset.css"));
+        assert_eq!(
+            resolve(4, "../styles/variables"),
+            some("src/styles/_variables.scss")
+        );
+        assert_eq!(
+            resolve(4, "@styles/theme"),
+            some("src/styles/theme/_index.scss")
+        );
+        assert_eq!(resolve(0, "sass:math"), some("external sass:math"));
+        assert_eq!(
+            resolve(0, "~bootstrap/scss/grid"),
+            some("external bootstrap/scss/grid")
+        );
+        assert_eq!(resolve(0, "https://fonts.example.com/inter.css"), None);
+    }
+}
@@ -23,7 +23,7 @@ export default function ModuleSearch() {
       const searchResults = nodes.filter(
         (node) =>
           node.path_relative.toLowerCase().includes(lowercaseSearchTerm) ||
-          (node.node_type === "EXTERNAL" &&
+          ((node.node_type === "EXTERNAL" || node.node_type === "STDLIB") &&
             node.label.toLowerCase().includes(lowercaseSearchTerm)),
       );
       setResults(searchResults);
@@ -92,7 +92,9 @@ export default function ModuleSearch() {
                 className={`py-2 px-2 hover:bg-gray-100 cursor-pointer ${idx === selectedIdx ? "bg-gray-100" : ""}`}
                 onClick={() => handleSelectNode(result.id)}
               >
-                {result.node_type === "INTERNAL"
+                {result.node_type === "INTERNAL" ||
+                result.node_type === "STYLESHEET" ||
+                result.node_type === "ASSET"
                   ? result.path_relative
                   : result.label}
               </li>
@@ -1,19 +1,37 @@
+// Mirrors schema/graph.schema.json, which is generated from the Rust types.
+// Fields are only ever added, so optional fields may be missing in older graphs.
 export type Graph = {
+  schema_version?: number;
   title: string;
+  language?: string;
   nodes: NodeMeta[];
   edges: EdgeMeta[];
 };
 
 export type NodeMeta = {
   id: string;
-  node_type: "INTERNAL" | "EXTERNAL";
+  node_type:
+    | "INTERNAL"
+    | "EXTERNAL"
+    | "STDLIB"
+    | "GROUP"
+    | "STYLESHEET"
+    | "ASSET";
   label: string;
   path_absolute: string;
   path_relative: string;
+  size?: number;
+  afferent_coupling?: number;
+  efferent_coupling?: number;
+  instability?: number;
+  transitive_dependencies?: number;
+  depth?: number;
 };
 
 export type EdgeMeta = {
   id: string;
   source_id: string;
   target_id: string;
+  weight?: number;
+  kind?: "NORMAL" | "OPTIONAL" | "DEV" | "BUILD";
 };